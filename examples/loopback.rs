@@ -0,0 +1,76 @@
+//! client and responder on loopback , no privileges needed.
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+
+use embedded_dhcp_client::client::{Client, ClientConfig, Event, BUFFER_LEN};
+use embedded_dhcp_client::pool::LeasePool;
+use embedded_dhcp_client::server::Server;
+use embedded_dhcp_client::std_net::StdDhcpClient;
+use embedded_dhcp_client::transport::SocketSettings;
+use embedded_dhcp_client::{DHCPMessage, HType, HwAddress};
+
+const SERVER_PORT: u16 = 1067;
+const CLIENT_PORT: u16 = 1068;
+
+fn main() {
+    // responder
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, SERVER_PORT)).unwrap();
+    std::thread::spawn(move || {
+        let server = Server {
+            server_ip_addr: [192, 168, 0, 1],
+            subnet_mask: [255, 255, 255, 0],
+            router: &[[192, 168, 0, 1]],
+            dns: &[[192, 168, 0, 1]],
+            ntp: &[],
+            domain_name: None,
+            broadcast_addr: None,
+            vendor_specific_information: None,
+            lease_time: 20,
+        };
+        let mut pool: LeasePool<8> = LeasePool::new([192, 168, 0, 100], [192, 168, 0, 107], &[]);
+        let start = std::time::Instant::now();
+        let mut recv_buffer = [0; BUFFER_LEN];
+        let mut send_buffer = [0; BUFFER_LEN];
+        loop {
+            let (len, _) = socket.recv_from(&mut recv_buffer).unwrap();
+            let mut decode_buffer = [0; BUFFER_LEN * 3 / 2];
+            let Some(request) = DHCPMessage::decode(&recv_buffer[..len], &mut decode_buffer) else {
+                continue;
+            };
+            let now = start.elapsed().as_secs() as u32;
+            if let Some(reply) = server.handle(&mut pool, &request, now) {
+                let len = reply.message.encode(&mut send_buffer).unwrap();
+                socket
+                    .send_to(&send_buffer[..len], (Ipv4Addr::LOCALHOST, CLIENT_PORT))
+                    .unwrap();
+            }
+        }
+    });
+
+    // client
+    let mac_addr = [2, 0, 0, 0, 0, 1];
+    let parameter_request_list = [1, 3, 6, 51, 58, 59];
+    let config = ClientConfig {
+        parameter_request_list: &parameter_request_list,
+        ..ClientConfig::new(HwAddress::Ethernet(&mac_addr), HType::Ethernet)
+    }
+    .with_receive_buffer_len(BUFFER_LEN);
+    let mut client = StdDhcpClient::bind(
+        Client::new(config),
+        SocketAddr::from((Ipv4Addr::LOCALHOST, CLIENT_PORT)),
+        rand::random(),
+    )
+    .unwrap()
+    .with_settings(
+        SocketSettings::default()
+            .with_server_port(SERVER_PORT)
+            .with_broadcast_addr(Ipv4Addr::LOCALHOST)
+            .with_server_addr(Ipv4Addr::LOCALHOST),
+    );
+    // the first lease , then its renewal.
+    for _ in 0..2 {
+        match client.next_event().unwrap() {
+            Event::Configured(lease) => println!("configured {:?}", lease),
+            Event::Deconfigured => println!("deconfigured"),
+        }
+    }
+}
@@ -1,11 +1,9 @@
-use std::mem::size_of;
-
+use embedded_dhcp_client::client::ClientConfig;
 use embedded_dhcp_client::DHCPMessage;
-use embedded_dhcp_client::DHCPMessageRaw;
-use embedded_dhcp_client::DecodeBuffer;
 use embedded_dhcp_client::HType;
 use embedded_dhcp_client::HwAddress;
 use embedded_dhcp_client::Options;
+use embedded_dhcp_client::DEFAULT_VENDOR_CLASS;
 use rand::{self, RngCore};
 fn main() {
     // create socket
@@ -22,26 +20,27 @@ fn main() {
     let hw_addr = HwAddress::Ethernet(&mac_addr);
     // fill parameter request list.
     let parameter_request_list = [1, 3, 6, 51, 58, 59];
+    // buffers for a 1500 bytes MTU , less IP and UDP headers.
+    let mut send_buffer = [0; 1472];
+    let mut recv_buffer = [0; 1472];
+    let mut decode_buffer = [0; 2048];
+    let config = ClientConfig {
+        vendor_class: Some(DEFAULT_VENDOR_CLASS),
+        parameter_request_list: &parameter_request_list,
+        ..ClientConfig::new(hw_addr, HType::Ethernet)
+    }
+    .with_receive_buffer_len(recv_buffer.len());
 
-    let discover_message =
-        DHCPMessage::new_discover(xid, hw_addr, HType::Ethernet, &parameter_request_list);
-    let discover_message: DHCPMessageRaw = discover_message.into();
-
-    let message: [u8; std::mem::size_of::<DHCPMessageRaw>()] =
-        unsafe { std::mem::transmute(discover_message) };
+    let discover_message = config.discover(xid);
+    let len = discover_message.encode(&mut send_buffer).unwrap();
     socket
-        .send_to(
-            &message[0..(size_of::<DHCPMessageRaw>() - size_of::<usize>())],
-            "255.255.255.255:67",
-        )
+        .send_to(&send_buffer[0..len], "255.255.255.255:67")
         .unwrap();
 
-    let mut recv_buffer = [0; 556];
     let (offer_message_size, addr) = socket.recv_from(&mut recv_buffer).unwrap();
     println!("received {} bytes from {}", offer_message_size, addr);
     let offer_message =
-        unsafe { DHCPMessageRaw::decode_received_message(&recv_buffer, offer_message_size) };
-    let offer_message = offer_message.decode_to_rustic_message(DecodeBuffer::Offer);
+        DHCPMessage::decode(&recv_buffer[0..offer_message_size], &mut decode_buffer).unwrap();
     println!("{:?}", offer_message);
     let xid = offer_message.xid;
     let offered_ip_addr = offer_message.yiaddr;
@@ -52,30 +51,19 @@ fn main() {
         .unwrap()
     {
         println!("offered ip address {:?}", offered_ip_addr);
-        let request_message = DHCPMessage::new_request(
-            xid,
-            hw_addr,
-            HType::Ethernet,
-            offered_ip_addr,
-            *server_ip_addr,
-        );
-        let request_message: DHCPMessageRaw = request_message.into();
-        let message: [u8; std::mem::size_of::<DHCPMessageRaw>()] =
-            unsafe { std::mem::transmute(request_message) };
+        let request_message = config.request(xid, offered_ip_addr, server_ip_addr);
+        let len = request_message.encode(&mut send_buffer).unwrap();
 
         println!("built request message");
         socket
-            .send_to(
-                &message[0..(size_of::<DHCPMessageRaw>() - size_of::<usize>())],
-                "255.255.255.255:67",
-            )
+            .send_to(&send_buffer[0..len], "255.255.255.255:67")
             .unwrap();
         println!("send request message");
         let (ack_message_size, addr) = socket.recv_from(&mut recv_buffer).unwrap();
         println!("received {} bytes from {}", ack_message_size, addr);
+        let mut decode_buffer = [0; 2048];
         let ack_message =
-            unsafe { DHCPMessageRaw::decode_received_message(&recv_buffer, ack_message_size) };
-        let ack_message = ack_message.decode_to_rustic_message(DecodeBuffer::Ack);
+            DHCPMessage::decode(&recv_buffer[0..ack_message_size], &mut decode_buffer).unwrap();
         println!("{:?}", ack_message);
     }
 }
@@ -0,0 +1,732 @@
+//! client side state machine (RFC 2131 4.4).
+//!
+//! this module does no I/O. feed every received message to [`Client::handle`]
+//! and send whatever it gives back , and call [`Client::poll`] at
+//! [`Client::poll_at`] for renewal. time is seconds from any epoch the caller
+//! likes.
+use crate::client_id::ClientId;
+use crate::dns::ClientFqdn;
+use crate::route::Route;
+use crate::server::Destination;
+use crate::vendor::VendorClass;
+use crate::{DHCPMessage, HType, HwAddress, MessageTy, Op, Options, UserClass};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Init,
+    Selecting,
+    Requesting,
+    Bound,
+    /// asking the server which gave the lease to extend it.
+    Renewing,
+    /// asking any server to extend the lease.
+    Rebinding,
+}
+
+/// configuration given by the server.
+#[derive(Clone, Copy, Debug)]
+pub struct Lease {
+    pub your_ip_addr: [u8; 4],
+    /// server identifier , or the boot server for BOOTP.
+    pub server_ip_addr: [u8; 4],
+    pub subnet_mask: Option<[u8; 4]>,
+    /// first router of option 3 , None when the server sent classless static
+    /// routes , see [`Lease::routes`].
+    pub router: Option<[u8; 4]>,
+    /// first ones of option 6.
+    pub dns_servers: [Option<[u8; 4]>; 3],
+    /// first ones of option 121 , or 249 without it. 0.0.0.0/0 is the default
+    /// route.
+    pub routes: [Option<Route>; 4],
+    /// None means permanent , always so for BOOTP.
+    pub lease_time: Option<u32>,
+    pub renewal_time: Option<u32>,
+    pub rebinding_time: Option<u32>,
+}
+
+impl Lease {
+    /// T1 , half the lease time when the server did not say.
+    pub fn renew_after(&self) -> Option<u32> {
+        self.renewal_time.or(self.lease_time.map(|x| x / 2))
+    }
+
+    /// T2 , 7/8 of the lease time when the server did not say.
+    pub fn rebind_after(&self) -> Option<u32> {
+        self.rebinding_time
+            .or(self.lease_time.map(|x| (x as u64 * 7 / 8) as u32))
+    }
+
+    fn from_ack(message: &DHCPMessage) -> Option<Self> {
+        let server_ip_addr = message.server_identifier()?;
+        Some(Lease {
+            server_ip_addr,
+            ..Self::from_reply(message)
+        })
+    }
+
+    fn from_bootp_reply(message: &DHCPMessage) -> Self {
+        Lease {
+            server_ip_addr: message.siaddr,
+            lease_time: None,
+            ..Self::from_reply(message)
+        }
+    }
+
+    fn from_reply(message: &DHCPMessage) -> Self {
+        let mut lease = Lease {
+            your_ip_addr: message.yiaddr,
+            server_ip_addr: [0; 4],
+            subnet_mask: None,
+            router: None,
+            dns_servers: [None; 3],
+            routes: [None; 4],
+            lease_time: None,
+            renewal_time: None,
+            rebinding_time: None,
+        };
+        for option in message.options.iter() {
+            match option {
+                Options::SubNetMask(x) => lease.subnet_mask = Some(x),
+                Options::Router(x) => lease.router = x.first().copied(),
+                Options::DNS(x) => x
+                    .iter()
+                    .zip(lease.dns_servers.iter_mut())
+                    .for_each(|(x, cell)| *cell = Some(*x)),
+                // 0xffffffff is infinity.
+                Options::IPAddressLeaseTime(x) => {
+                    lease.lease_time = Some(x).filter(|x| *x != u32::MAX)
+                }
+                Options::RenewalTime(x) => lease.renewal_time = Some(x),
+                Options::RebindingTime(x) => lease.rebinding_time = Some(x),
+                _ => {}
+            }
+        }
+        // RFC 3442 : option 3 is ignored when option 121 is present.
+        if let Some(routes) = message.classless_static_route() {
+            routes
+                .iter()
+                .zip(lease.routes.iter_mut())
+                .for_each(|(x, cell)| *cell = Some(x));
+            lease.router = None;
+        }
+        lease
+    }
+}
+
+/// what a network driver reports.
+#[derive(Clone, Copy, Debug)]
+pub enum Event {
+    /// a lease was obtained or extended.
+    Configured(Lease),
+    /// the lease was lost , stop using the address.
+    Deconfigured,
+}
+
+/// largest message sent or received by the network drivers , a 1500 bytes
+/// MTU less IP and UDP headers.
+///
+/// give it to [`ClientConfig::with_receive_buffer_len`].
+pub const BUFFER_LEN: usize = 1472;
+
+/// who the client is and what it asks for , every message it sends is built
+/// from this.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientConfig<'a> {
+    pub hardware_address: HwAddress<'a>,
+    pub hw_type: HType,
+    /// option 61 , None sends the hardware type and address.
+    pub client_id: Option<&'a ClientId>,
+    /// option 12.
+    pub hostname: Option<&'a str>,
+    /// option 60.
+    pub vendor_class: Option<&'a str>,
+    /// option 77.
+    pub user_class: Option<UserClass<'a>>,
+    /// option 55 , not sent when empty.
+    pub parameter_request_list: &'a [u8],
+    /// option 57 , the largest message we can receive. servers send at most
+    /// 576 bytes without it , see [`ClientConfig::with_receive_buffer_len`].
+    pub max_message_size: Option<u16>,
+    /// option 51 in DISCOVER and REQUEST.
+    pub requested_lease_time: Option<u32>,
+    /// ask servers to broadcast replies , for stacks which can not receive
+    /// unicast before the address is configured.
+    pub broadcast: bool,
+}
+
+impl<'a> ClientConfig<'a> {
+    pub fn new(hardware_address: HwAddress<'a>, hw_type: HType) -> Self {
+        Self {
+            hardware_address,
+            hw_type,
+            client_id: None,
+            hostname: None,
+            vendor_class: None,
+            user_class: None,
+            parameter_request_list: &[],
+            max_message_size: None,
+            requested_lease_time: None,
+            broadcast: true,
+        }
+    }
+
+    /// advertise option 57 for a receive buffer of `len` bytes , e.g. the link
+    /// MTU less IP and UDP headers.
+    pub fn with_receive_buffer_len(mut self, len: usize) -> Self {
+        // option 57 counts IP and UDP headers , and is at least 576.
+        let size = u16::try_from(len + 28).unwrap_or(u16::MAX);
+        self.max_message_size = Some(size.max(576));
+        self
+    }
+
+    pub fn discover(&self, transaction_id: u32) -> DHCPMessage<'a> {
+        let message = self.message(transaction_id, MessageTy::Discover);
+        self.with_parameters(message)
+    }
+
+    pub fn request(
+        &self,
+        transaction_id: u32,
+        offered_ip_addr: [u8; 4],
+        server_ip_addr: [u8; 4],
+    ) -> DHCPMessage<'a> {
+        let mut message = self.message(transaction_id, MessageTy::Request);
+        message.insert_option(Options::RequestedIPAddress(offered_ip_addr));
+        message.insert_option(Options::ServerIdentifer(server_ip_addr));
+        self.with_parameters(message)
+    }
+
+    /// REQUEST to extend the lease of `ip_addr` , in RENEWING and REBINDING.
+    pub fn renew(&self, transaction_id: u32, ip_addr: [u8; 4]) -> DHCPMessage<'a> {
+        let mut message = self.message(transaction_id, MessageTy::Request);
+        message.ciaddr = ip_addr;
+        // we can receive unicast now.
+        message.flags = false;
+        self.with_parameters(message)
+    }
+
+    /// give `ip_addr` back to the server which leased it.
+    pub fn release(
+        &self,
+        transaction_id: u32,
+        ip_addr: [u8; 4],
+        server_ip_addr: [u8; 4],
+    ) -> DHCPMessage<'a> {
+        // RFC 2131 table 5 : no hostname , vendor class or parameters.
+        let mut message = self.header(transaction_id, MessageTy::Release);
+        message.ciaddr = ip_addr;
+        message.flags = false;
+        message.insert_option(Options::ServerIdentifer(server_ip_addr));
+        message
+    }
+
+    /// BOOTREQUEST without any option , for BOOTP only servers.
+    pub fn bootp_request(&self, transaction_id: u32) -> DHCPMessage<'a> {
+        DHCPMessage {
+            flags: self.broadcast,
+            ..DHCPMessage::new_bootp_request(transaction_id, self.hardware_address, self.hw_type)
+        }
+    }
+
+    /// header , message type and who we are.
+    fn message(&self, transaction_id: u32, message_type: MessageTy) -> DHCPMessage<'a> {
+        let mut message = self.header(transaction_id, message_type);
+        if let Some(hostname) = self.hostname {
+            message.insert_option(Options::Hostname(hostname));
+        }
+        if let Some(vendor_class) = self.vendor_class {
+            message.insert_option(Options::VendorClassIdentifier(vendor_class));
+        }
+        if let Some(user_class) = self.user_class {
+            message.insert_option(Options::UserClass(user_class));
+        }
+        message
+    }
+
+    /// header , message type and client identifier.
+    ///
+    /// the messages of the client have 14 options at most , End included , so
+    /// every insert_option has room.
+    fn header(&self, transaction_id: u32, message_type: MessageTy) -> DHCPMessage<'a> {
+        let (htype, _) = self.hw_type.into();
+        let mut message = DHCPMessage {
+            flags: self.broadcast,
+            ..DHCPMessage::new_boot_request(
+                transaction_id,
+                self.hardware_address,
+                self.hw_type,
+                &[Options::DHCPMessageType(message_type)],
+            )
+        };
+        message.insert_option(match self.client_id {
+            Some(client_id) => client_id.option(),
+            None => Options::ClientIdentifier(htype, self.hardware_address.addr()),
+        });
+        message
+    }
+
+    /// what we ask the server for.
+    fn with_parameters(&self, mut message: DHCPMessage<'a>) -> DHCPMessage<'a> {
+        if !self.parameter_request_list.is_empty() {
+            message.insert_option(Options::ParameterRequestList(self.parameter_request_list));
+        }
+        if let Some(max_message_size) = self.max_message_size {
+            message.insert_option(Options::MaximumDHCPMessageSize(max_message_size));
+        }
+        if let Some(lease_time) = self.requested_lease_time {
+            message.insert_option(Options::IPAddressLeaseTime(lease_time));
+        }
+        message
+    }
+}
+
+pub struct Client<'a> {
+    state: State,
+    xid: u32,
+    config: ClientConfig<'a>,
+    rapid_commit: bool,
+    bootp: bool,
+    vendor_class: Option<VendorClass<'a>>,
+    client_fqdn: Option<ClientFqdn<'a>>,
+    lease: Option<Lease>,
+    bound_at: u32,
+    retransmit_at: u32,
+}
+
+impl<'a> Client<'a> {
+    pub fn new(config: ClientConfig<'a>) -> Self {
+        Self {
+            state: State::Init,
+            xid: 0,
+            config,
+            rapid_commit: false,
+            bootp: false,
+            vendor_class: None,
+            client_fqdn: None,
+            lease: None,
+            bound_at: 0,
+            retransmit_at: 0,
+        }
+    }
+
+    /// send Rapid Commit in DISCOVER and accept a direct ACK (RFC 4039).
+    pub fn with_rapid_commit(mut self) -> Self {
+        self.rapid_commit = true;
+        self
+    }
+
+    /// send BOOTREQUEST and accept a permanent address from a BOOTP server.
+    pub fn with_bootp(mut self) -> Self {
+        self.bootp = true;
+        self
+    }
+
+    /// send option 124 in DISCOVER and REQUEST.
+    pub fn with_vendor_class(mut self, vendor_class: VendorClass<'a>) -> Self {
+        self.vendor_class = Some(vendor_class);
+        self
+    }
+
+    /// send option 81 in DISCOVER and REQUEST.
+    pub fn with_client_fqdn(mut self, client_fqdn: ClientFqdn<'a>) -> Self {
+        self.client_fqdn = Some(client_fqdn);
+        self
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn config(&self) -> &ClientConfig<'a> {
+        &self.config
+    }
+
+    pub fn lease(&self) -> Option<&Lease> {
+        self.lease.as_ref()
+    }
+
+    /// start (or restart) configuration with a new transaction id.
+    pub fn discover(&mut self, transaction_id: u32) -> DHCPMessage<'a> {
+        self.xid = transaction_id;
+        self.lease = None;
+        self.state = State::Selecting;
+        if self.bootp {
+            return self.config.bootp_request(transaction_id);
+        }
+        let mut message = self.config.discover(transaction_id);
+        if self.rapid_commit {
+            message.insert_option(Options::RapidCommit);
+        }
+        self.with_identity(message)
+    }
+
+    /// when [`Client::poll`] has something to do , None without a lease or
+    /// for a permanent one.
+    pub fn poll_at(&self) -> Option<u32> {
+        let lease = self.lease.as_ref()?;
+        match self.state {
+            State::Bound => Some(self.bound_at.saturating_add(lease.renew_after()?)),
+            State::Renewing | State::Rebinding => Some(self.retransmit_at),
+            _ => None,
+        }
+    }
+
+    /// renew and rebind timers (RFC 2131 4.4.5).
+    ///
+    /// returns a REQUEST to send to [`Client::destination`]. when the lease
+    /// expires the state goes back to Init , then call [`Client::discover`].
+    pub fn poll(&mut self, now: u32) -> Option<DHCPMessage<'a>> {
+        let lease = self.lease?;
+        let at = |x: Option<u32>| x.map(|x| self.bound_at.saturating_add(x));
+        let (renew, rebind, expire) = (
+            at(lease.renew_after())?,
+            at(lease.rebind_after())?,
+            at(lease.lease_time)?,
+        );
+        if now >= expire {
+            self.lease = None;
+            self.state = State::Init;
+            return None;
+        }
+        if self.state == State::Bound && now >= renew {
+            self.state = State::Renewing;
+            self.retransmit_at = now;
+        }
+        if self.state == State::Renewing && now >= rebind {
+            self.state = State::Rebinding;
+            self.retransmit_at = now;
+        }
+        let deadline = match self.state {
+            State::Renewing => rebind,
+            State::Rebinding => expire,
+            _ => return None,
+        };
+        if now < self.retransmit_at {
+            return None;
+        }
+        // wait half the time left , at least 60 seconds.
+        self.retransmit_at = now.saturating_add(((deadline - now) / 2).max(60));
+        Some(self.with_identity(self.config.renew(self.xid, lease.your_ip_addr)))
+    }
+
+    /// where to send the messages , the server only while renewing.
+    pub fn destination(&self) -> Destination {
+        match (self.state, self.lease) {
+            (State::Renewing, Some(lease)) => Destination::Unicast(lease.server_ip_addr),
+            _ => Destination::Broadcast,
+        }
+    }
+
+    /// give the lease back , e.g. on shutdown.
+    ///
+    /// returns a RELEASE to send to the server , the state goes back to Init.
+    pub fn release(&mut self) -> Option<(DHCPMessage<'a>, Destination)> {
+        let lease = self.lease.take()?;
+        let state = core::mem::replace(&mut self.state, State::Init);
+        if !matches!(state, State::Bound | State::Renewing | State::Rebinding) || self.bootp {
+            return None;
+        }
+        let message = self
+            .config
+            .release(self.xid, lease.your_ip_addr, lease.server_ip_addr);
+        Some((message, Destination::Unicast(lease.server_ip_addr)))
+    }
+
+    /// process a reply from a server received at `now`.
+    ///
+    /// returns the message to send next , if any.
+    /// messages which does not belong to this transaction are ignored.
+    pub fn handle(&mut self, message: &DHCPMessage, now: u32) -> Option<DHCPMessage<'a>> {
+        if !matches!(message.op, Op::BootReply) || message.xid != self.xid {
+            return None;
+        }
+        if self.bootp {
+            if self.state == State::Selecting && message.is_bootp() {
+                self.lease = Some(Lease::from_bootp_reply(message));
+                self.state = State::Bound;
+                self.bound_at = now;
+            }
+            return None;
+        }
+        match (self.state, message.message_type()?) {
+            (State::Selecting, MessageTy::Offer) => {
+                // also the path for servers which ignore Rapid Commit.
+                let server_ip_addr = message.server_identifier()?;
+                self.state = State::Requesting;
+                Some(
+                    self.with_identity(self.config.request(
+                        self.xid,
+                        message.yiaddr,
+                        server_ip_addr,
+                    )),
+                )
+            }
+            // RFC 4039 : ACK without Rapid Commit is discarded in SELECTING.
+            (State::Selecting, MessageTy::Ack)
+                if self.rapid_commit && message.has_rapid_commit() =>
+            {
+                self.bind(message, now);
+                None
+            }
+            (State::Requesting | State::Renewing | State::Rebinding, MessageTy::Ack) => {
+                self.bind(message, now);
+                None
+            }
+            (State::Requesting | State::Renewing | State::Rebinding, MessageTy::Nak) => {
+                self.lease = None;
+                self.state = State::Init;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// options telling who we are.
+    fn with_identity(&self, mut message: DHCPMessage<'a>) -> DHCPMessage<'a> {
+        if let Some(vendor_class) = self.vendor_class {
+            message.insert_option(Options::VendorIdentifyingVendorClass(vendor_class));
+        }
+        if let Some(client_fqdn) = self.client_fqdn {
+            message.insert_option(Options::ClientFqdn(client_fqdn));
+        }
+        message
+    }
+
+    fn bind(&mut self, message: &DHCPMessage, now: u32) {
+        match Lease::from_ack(message) {
+            Some(lease) => {
+                self.lease = Some(lease);
+                self.state = State::Bound;
+                self.bound_at = now;
+            }
+            None => {
+                self.lease = None;
+                self.state = State::Init;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::option_list;
+
+    const MAC_ADDR: [u8; 6] = [2, 0, 0, 0, 0, 1];
+    const SERVER: [u8; 4] = [192, 168, 0, 1];
+    const ADDRESS: [u8; 4] = [192, 168, 0, 100];
+
+    fn config() -> ClientConfig<'static> {
+        ClientConfig::new(HwAddress::Ethernet(&MAC_ADDR), HType::Ethernet)
+    }
+
+    /// a BOOTREPLY offering [`ADDRESS`].
+    fn reply<'a>(xid: u32, options: &[Options<'a>]) -> DHCPMessage<'a> {
+        DHCPMessage {
+            op: Op::BootReply,
+            xid,
+            yiaddr: ADDRESS,
+            siaddr: SERVER,
+            flags: false,
+            options: option_list(options),
+            ..config().header(xid, MessageTy::Offer)
+        }
+    }
+
+    fn has(message: &DHCPMessage, f: impl Fn(&Options) -> bool) -> bool {
+        message.options.iter().any(|x| f(&x))
+    }
+
+    #[test]
+    fn offer_request_ack() {
+        let mut client = Client::new(config());
+        let discover = client.discover(7);
+        assert_eq!(discover.message_type(), Some(MessageTy::Discover));
+        assert!(!discover.has_rapid_commit());
+        let offer = reply(
+            7,
+            &[
+                Options::DHCPMessageType(MessageTy::Offer),
+                Options::ServerIdentifer(SERVER),
+            ],
+        );
+        // another transaction.
+        assert!(client.handle(&reply(8, &[]), 0).is_none());
+        let request = client.handle(&offer, 0).unwrap();
+        assert_eq!(client.state(), State::Requesting);
+        assert_eq!(request.message_type(), Some(MessageTy::Request));
+        assert_eq!(request.requested_ip_addr(), Some(ADDRESS));
+        assert_eq!(request.server_identifier(), Some(SERVER));
+        let nak = reply(
+            7,
+            &[
+                Options::DHCPMessageType(MessageTy::Nak),
+                Options::ServerIdentifer(SERVER),
+            ],
+        );
+        assert!(client.handle(&nak, 1).is_none());
+        assert_eq!(client.state(), State::Init);
+        assert!(client.lease().is_none());
+    }
+
+    #[test]
+    fn rapid_commit() {
+        let ack = reply(
+            7,
+            &[
+                Options::DHCPMessageType(MessageTy::Ack),
+                Options::ServerIdentifer(SERVER),
+                Options::IPAddressLeaseTime(3600),
+                Options::RapidCommit,
+            ],
+        );
+        // not asked for , discarded.
+        let mut client = Client::new(config());
+        client.discover(7);
+        assert!(client.handle(&ack, 0).is_none());
+        assert_eq!(client.state(), State::Selecting);
+
+        let mut client = Client::new(config()).with_rapid_commit();
+        assert!(client.discover(7).has_rapid_commit());
+        assert!(client.handle(&ack, 10).is_none());
+        assert_eq!(client.state(), State::Bound);
+        let lease = client.lease().unwrap();
+        assert_eq!(lease.your_ip_addr, ADDRESS);
+        assert_eq!(lease.server_ip_addr, SERVER);
+        assert_eq!(client.poll_at(), Some(10 + 1800));
+
+        // an ACK without Rapid Commit while selecting.
+        let mut client = Client::new(config()).with_rapid_commit();
+        client.discover(7);
+        let ack = reply(
+            7,
+            &[
+                Options::DHCPMessageType(MessageTy::Ack),
+                Options::ServerIdentifer(SERVER),
+            ],
+        );
+        assert!(client.handle(&ack, 0).is_none());
+        assert_eq!(client.state(), State::Selecting);
+
+        // the server ignores Rapid Commit.
+        let offer = reply(
+            7,
+            &[
+                Options::DHCPMessageType(MessageTy::Offer),
+                Options::ServerIdentifer(SERVER),
+            ],
+        );
+        let request = client.handle(&offer, 0).unwrap();
+        assert_eq!(request.message_type(), Some(MessageTy::Request));
+        assert!(!request.has_rapid_commit());
+        assert!(client.handle(&ack, 0).is_none());
+        assert_eq!(client.state(), State::Bound);
+    }
+
+    #[test]
+    fn bootp() {
+        let mut client = Client::new(config()).with_bootp();
+        let request = client.discover(7);
+        assert!(request.is_bootp());
+        assert!(request.flags);
+        assert_eq!(request.chaddr[..6], MAC_ADDR);
+        // a DHCP reply is not for us.
+        let offer = reply(
+            7,
+            &[
+                Options::DHCPMessageType(MessageTy::Offer),
+                Options::ServerIdentifer(SERVER),
+            ],
+        );
+        assert!(client.handle(&offer, 0).is_none());
+        assert_eq!(client.state(), State::Selecting);
+        let reply = reply(7, &[Options::SubNetMask([255, 255, 255, 0])]);
+        assert!(client.handle(&reply, 0).is_none());
+        assert_eq!(client.state(), State::Bound);
+        let lease = client.lease().unwrap();
+        assert_eq!(lease.your_ip_addr, ADDRESS);
+        assert_eq!(lease.server_ip_addr, SERVER);
+        assert_eq!(lease.subnet_mask, Some([255, 255, 255, 0]));
+        // permanent , nothing to renew or release.
+        assert_eq!(lease.lease_time, None);
+        assert_eq!(client.poll_at(), None);
+        assert!(client.release().is_none());
+    }
+
+    #[test]
+    fn client_id() {
+        let client = Client::new(config());
+        assert!(has(&client.config().discover(1), |x| matches!(
+            x,
+            Options::ClientIdentifier(1, id) if *id == MAC_ADDR
+        )));
+        let client_id = ClientId::duid_ll(1, 1, &MAC_ADDR).unwrap();
+        let config = ClientConfig {
+            client_id: Some(&client_id),
+            ..config()
+        };
+        for message in [
+            config.discover(1),
+            config.request(1, ADDRESS, SERVER),
+            config.renew(1, ADDRESS),
+            config.release(1, ADDRESS, SERVER),
+        ] {
+            assert!(has(&message, |x| matches!(
+                x,
+                Options::ClientIdentifier(255, id) if *id == client_id.id()
+            )));
+        }
+    }
+
+    #[test]
+    fn client_config() {
+        // option 57 is at least 576.
+        assert_eq!(
+            config().with_receive_buffer_len(0).max_message_size,
+            Some(576)
+        );
+        let config = ClientConfig {
+            hostname: Some("host"),
+            vendor_class: Some("class"),
+            parameter_request_list: &[1, 3, 6],
+            requested_lease_time: Some(3600),
+            broadcast: false,
+            ..config().with_receive_buffer_len(BUFFER_LEN)
+        };
+        assert_eq!(config.max_message_size, Some(1500));
+        let discover = config.discover(1);
+        assert!(!discover.flags);
+        assert!(has(&discover, |x| matches!(x, Options::Hostname("host"))));
+        assert!(has(&discover, |x| matches!(
+            x,
+            Options::VendorClassIdentifier("class")
+        )));
+        assert!(has(&discover, |x| matches!(
+            x,
+            Options::ParameterRequestList([1, 3, 6])
+        )));
+        assert!(has(&discover, |x| matches!(
+            x,
+            Options::MaximumDHCPMessageSize(1500)
+        )));
+        assert!(has(&discover, |x| matches!(
+            x,
+            Options::IPAddressLeaseTime(3600)
+        )));
+        let renew = config.renew(1, ADDRESS);
+        assert_eq!(renew.ciaddr, ADDRESS);
+        assert_eq!(renew.requested_ip_addr(), None);
+        // RFC 2131 table 5.
+        let release = config.release(1, ADDRESS, SERVER);
+        assert_eq!(release.message_type(), Some(MessageTy::Release));
+        assert_eq!(release.ciaddr, ADDRESS);
+        assert_eq!(release.server_identifier(), Some(SERVER));
+        assert!(!has(&release, |x| matches!(
+            x,
+            Options::Hostname(_)
+                | Options::VendorClassIdentifier(_)
+                | Options::ParameterRequestList(_)
+                | Options::IPAddressLeaseTime(_)
+        )));
+    }
+}
@@ -0,0 +1,308 @@
+//! client identifiers of option 61 , node-specific ones with IAID and DUID
+//! (RFC 4361) so DHCPv4 and DHCPv6 see the same client.
+use crate::Options;
+
+/// longest identifier : type , IAID and a DUID of 130 bytes.
+pub const CLIENT_ID_LEN: usize = 4 + 130;
+
+/// DHCP Unique Identifier (RFC 8415 11).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Duid<'a> {
+    /// link-layer address plus time , seconds since 2000-01-01 UTC.
+    Llt {
+        hw_type: u16,
+        time: u32,
+        link_layer_addr: &'a [u8],
+    },
+    /// assigned by the vendor.
+    En {
+        enterprise: u32,
+        identifier: &'a [u8],
+    },
+    /// link-layer address.
+    Ll {
+        hw_type: u16,
+        link_layer_addr: &'a [u8],
+    },
+    /// see RFC 6355 .
+    Uuid([u8; 16]),
+    Unknown(u16, &'a [u8]),
+}
+
+impl<'a> Duid<'a> {
+    pub fn decode(bytes: &'a [u8]) -> Option<Self> {
+        let [a, b, rest @ ..] = bytes else {
+            return None;
+        };
+        Some(match (u16::from_be_bytes([*a, *b]), rest) {
+            (1, [a, b, c, d, e, f, link_layer_addr @ ..]) => Duid::Llt {
+                hw_type: u16::from_be_bytes([*a, *b]),
+                time: u32::from_be_bytes([*c, *d, *e, *f]),
+                link_layer_addr,
+            },
+            (2, [a, b, c, d, identifier @ ..]) => Duid::En {
+                enterprise: u32::from_be_bytes([*a, *b, *c, *d]),
+                identifier,
+            },
+            (3, [a, b, link_layer_addr @ ..]) => Duid::Ll {
+                hw_type: u16::from_be_bytes([*a, *b]),
+                link_layer_addr,
+            },
+            (4, rest) if rest.len() == 16 => {
+                let mut uuid = [0; 16];
+                uuid.copy_from_slice(rest);
+                Duid::Uuid(uuid)
+            }
+            (ty, rest) => Duid::Unknown(ty, rest),
+        })
+    }
+
+    /// write the DUID , returns the length. None if longer than 130 bytes.
+    pub fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        let mut header = [0; 8];
+        let (header_len, data): (usize, &[u8]) = match self {
+            Duid::Llt {
+                hw_type,
+                time,
+                link_layer_addr,
+            } => {
+                header[0..2].copy_from_slice(&1u16.to_be_bytes());
+                header[2..4].copy_from_slice(&hw_type.to_be_bytes());
+                header[4..8].copy_from_slice(&time.to_be_bytes());
+                (8, link_layer_addr)
+            }
+            Duid::En {
+                enterprise,
+                identifier,
+            } => {
+                header[0..2].copy_from_slice(&2u16.to_be_bytes());
+                header[2..6].copy_from_slice(&enterprise.to_be_bytes());
+                (6, identifier)
+            }
+            Duid::Ll {
+                hw_type,
+                link_layer_addr,
+            } => {
+                header[0..2].copy_from_slice(&3u16.to_be_bytes());
+                header[2..4].copy_from_slice(&hw_type.to_be_bytes());
+                (4, link_layer_addr)
+            }
+            Duid::Uuid(uuid) => {
+                header[0..2].copy_from_slice(&4u16.to_be_bytes());
+                (2, uuid)
+            }
+            Duid::Unknown(ty, data) => {
+                header[0..2].copy_from_slice(&ty.to_be_bytes());
+                (2, data)
+            }
+        };
+        let len = header_len + data.len();
+        if len > 130 {
+            return None;
+        }
+        let buffer = buffer.get_mut(..len)?;
+        buffer[..header_len].copy_from_slice(&header[..header_len]);
+        buffer[header_len..].copy_from_slice(data);
+        Some(len)
+    }
+}
+
+/// value of option 61 , kept by the caller while messages borrow it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientId {
+    ty: u8,
+    len: u8,
+    id: [u8; CLIENT_ID_LEN - 1],
+}
+
+impl ClientId {
+    /// any type and identifier , None if too long.
+    pub fn new(ty: u8, id: &[u8]) -> Option<Self> {
+        let mut bytes = [0; CLIENT_ID_LEN - 1];
+        bytes.get_mut(..id.len())?.copy_from_slice(id);
+        Some(Self {
+            ty,
+            len: id.len() as u8,
+            id: bytes,
+        })
+    }
+
+    /// hardware type and address , what RFC 2132 suggests.
+    pub fn hardware(hw_type: u8, hardware_address: &[u8]) -> Option<Self> {
+        Self::new(hw_type, hardware_address)
+    }
+
+    /// type 255 , IAID and DUID. None if the DUID is too long.
+    pub fn node_specific(iaid: u32, duid: &Duid) -> Option<Self> {
+        let mut id = [0; CLIENT_ID_LEN - 1];
+        id[0..4].copy_from_slice(&iaid.to_be_bytes());
+        let len = 4 + duid.encode(&mut id[4..])?;
+        Some(Self {
+            ty: 255,
+            len: len as u8,
+            id,
+        })
+    }
+
+    pub fn duid_llt(iaid: u32, hw_type: u16, time: u32, link_layer_addr: &[u8]) -> Option<Self> {
+        Self::node_specific(
+            iaid,
+            &Duid::Llt {
+                hw_type,
+                time,
+                link_layer_addr,
+            },
+        )
+    }
+
+    pub fn duid_en(iaid: u32, enterprise: u32, identifier: &[u8]) -> Option<Self> {
+        Self::node_specific(
+            iaid,
+            &Duid::En {
+                enterprise,
+                identifier,
+            },
+        )
+    }
+
+    pub fn duid_ll(iaid: u32, hw_type: u16, link_layer_addr: &[u8]) -> Option<Self> {
+        Self::node_specific(
+            iaid,
+            &Duid::Ll {
+                hw_type,
+                link_layer_addr,
+            },
+        )
+    }
+
+    pub fn duid_uuid(iaid: u32, uuid: [u8; 16]) -> Self {
+        let mut id = [0; CLIENT_ID_LEN - 1];
+        id[0..4].copy_from_slice(&iaid.to_be_bytes());
+        id[4..6].copy_from_slice(&4u16.to_be_bytes());
+        id[6..22].copy_from_slice(&uuid);
+        Self {
+            ty: 255,
+            len: 22,
+            id,
+        }
+    }
+
+    pub fn ty(&self) -> u8 {
+        self.ty
+    }
+
+    pub fn id(&self) -> &[u8] {
+        &self.id[..self.len as usize]
+    }
+
+    /// for a node-specific identifier.
+    pub fn iaid(&self) -> Option<u32> {
+        match (self.ty, self.id()) {
+            (255, [a, b, c, d, ..]) => Some(u32::from_be_bytes([*a, *b, *c, *d])),
+            _ => None,
+        }
+    }
+
+    /// for a node-specific identifier.
+    pub fn duid(&self) -> Option<Duid<'_>> {
+        match (self.ty, self.id()) {
+            (255, [_, _, _, _, duid @ ..]) => Duid::decode(duid),
+            _ => None,
+        }
+    }
+
+    pub fn option(&self) -> Options<'_> {
+        Options::ClientIdentifier(self.ty, self.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DHCPMessage, HType, HwAddress, HEADER_LEN};
+
+    const MAC_ADDR: [u8; 6] = [2, 0, 0, 0, 0, 1];
+
+    /// option 61 as sent , code and length included.
+    fn encoded<'b>(client_id: &ClientId, buffer: &'b mut [u8; 600]) -> &'b [u8] {
+        let mut message =
+            DHCPMessage::new_bootp_request(1, HwAddress::Ethernet(&MAC_ADDR), HType::Ethernet);
+        message.insert_option(client_id.option()).unwrap();
+        message.encode(buffer).unwrap();
+        let option = &buffer[HEADER_LEN + 4..];
+        &option[..2 + option[1] as usize]
+    }
+
+    #[test]
+    fn node_specific_encodings() {
+        let mut buffer = [0; 600];
+        let client_id = ClientId::duid_llt(0x01020304, 1, 0x0a0b0c0d, &MAC_ADDR).unwrap();
+        assert_eq!(
+            encoded(&client_id, &mut buffer),
+            [61, 19, 255, 1, 2, 3, 4, 0, 1, 0, 1, 0x0a, 0x0b, 0x0c, 0x0d, 2, 0, 0, 0, 0, 1]
+        );
+        let client_id = ClientId::duid_en(7, 32473, b"id").unwrap();
+        assert_eq!(
+            encoded(&client_id, &mut buffer),
+            [61, 13, 255, 0, 0, 0, 7, 0, 2, 0, 0, 0x7e, 0xd9, b'i', b'd']
+        );
+        let client_id = ClientId::duid_ll(7, 1, &MAC_ADDR).unwrap();
+        assert_eq!(
+            encoded(&client_id, &mut buffer),
+            [61, 15, 255, 0, 0, 0, 7, 0, 3, 0, 1, 2, 0, 0, 0, 0, 1]
+        );
+        let client_id = ClientId::duid_uuid(7, [0x55; 16]);
+        assert_eq!(
+            encoded(&client_id, &mut buffer)[..9],
+            [61, 23, 255, 0, 0, 0, 7, 0, 4]
+        );
+        assert_eq!(encoded(&client_id, &mut buffer)[9..], [0x55; 16]);
+        assert_eq!(client_id.iaid(), Some(7));
+        assert_eq!(client_id.duid(), Some(Duid::Uuid([0x55; 16])));
+    }
+
+    #[test]
+    fn hardware_encoding() {
+        let mut buffer = [0; 600];
+        let client_id = ClientId::hardware(1, &MAC_ADDR).unwrap();
+        assert_eq!(
+            encoded(&client_id, &mut buffer),
+            [61, 7, 1, 2, 0, 0, 0, 0, 1]
+        );
+        assert_eq!(client_id.iaid(), None);
+        assert_eq!(client_id.duid(), None);
+    }
+
+    #[test]
+    fn duid_round_trip() {
+        let duids = [
+            Duid::Llt {
+                hw_type: 1,
+                time: 5,
+                link_layer_addr: &MAC_ADDR,
+            },
+            Duid::En {
+                enterprise: 32473,
+                identifier: b"id",
+            },
+            Duid::Ll {
+                hw_type: 1,
+                link_layer_addr: &MAC_ADDR,
+            },
+            Duid::Uuid([0x55; 16]),
+            Duid::Unknown(9, &[1, 2]),
+        ];
+        for duid in duids {
+            let client_id = ClientId::node_specific(0x01020304, &duid).unwrap();
+            assert_eq!(client_id.ty(), 255);
+            assert_eq!(client_id.iaid(), Some(0x01020304));
+            assert_eq!(client_id.duid(), Some(duid));
+        }
+        // a DUID is at most 130 bytes.
+        let duid = Duid::En {
+            enterprise: 1,
+            identifier: &[0; 125],
+        };
+        assert!(ClientId::node_specific(1, &duid).is_none());
+    }
+}
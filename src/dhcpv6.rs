@@ -0,0 +1,726 @@
+//! DHCPv6 messages between clients and servers (RFC 8415 8 and 21).
+//!
+//! like the DHCPv4 codec this borrows the received bytes , decodes options
+//! when they are read and skips malformed ones. relay messages are not
+//! supported.
+use crate::client_id::Duid;
+use crate::dns::DomainName;
+
+pub const CLIENT_PORT: u16 = 546;
+pub const SERVER_PORT: u16 = 547;
+/// All_DHCP_Relay_Agents_and_Servers , where clients send.
+pub const ALL_SERVERS: [u8; 16] = [0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    Solicit,
+    Advertise,
+    Request,
+    Confirm,
+    Renew,
+    Rebind,
+    Reply,
+    Release,
+    Decline,
+    InformationRequest,
+}
+
+impl MessageType {
+    pub fn from_u8(x: u8) -> Option<Self> {
+        Some(match x {
+            1 => MessageType::Solicit,
+            2 => MessageType::Advertise,
+            3 => MessageType::Request,
+            4 => MessageType::Confirm,
+            5 => MessageType::Renew,
+            6 => MessageType::Rebind,
+            7 => MessageType::Reply,
+            8 => MessageType::Release,
+            9 => MessageType::Decline,
+            11 => MessageType::InformationRequest,
+            _ => return None,
+        })
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            MessageType::Solicit => 1,
+            MessageType::Advertise => 2,
+            MessageType::Request => 3,
+            MessageType::Confirm => 4,
+            MessageType::Renew => 5,
+            MessageType::Rebind => 6,
+            MessageType::Reply => 7,
+            MessageType::Release => 8,
+            MessageType::Decline => 9,
+            MessageType::InformationRequest => 11,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Message<'a> {
+    pub message_type: MessageType,
+    /// 24 bits.
+    pub transaction_id: u32,
+    pub options: DhcpOptions<'a>,
+}
+
+impl<'a> Message<'a> {
+    pub fn new(
+        message_type: MessageType,
+        transaction_id: u32,
+        options: &'a [DhcpOption<'a>],
+    ) -> Self {
+        Self {
+            message_type,
+            transaction_id: transaction_id & 0xff_ffff,
+            options: DhcpOptions::List(options),
+        }
+    }
+
+    /// None for relay messages and unknown types.
+    pub fn decode(bytes: &'a [u8]) -> Option<Self> {
+        let [ty, a, b, c, options @ ..] = bytes else {
+            return None;
+        };
+        Some(Self {
+            message_type: MessageType::from_u8(*ty)?,
+            transaction_id: u32::from_be_bytes([0, *a, *b, *c]),
+            options: DhcpOptions::Bytes(options),
+        })
+    }
+
+    /// returns the length , None if it does not fit.
+    pub fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        let header = buffer.get_mut(..4)?;
+        header[0] = self.message_type.to_u8();
+        header[1..4].copy_from_slice(&self.transaction_id.to_be_bytes()[1..]);
+        Some(4 + self.options.encode(&mut buffer[4..])?)
+    }
+
+    pub fn client_id(&self) -> Option<Duid<'a>> {
+        self.options.iter().find_map(|x| match x {
+            DhcpOption::ClientId(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn server_id(&self) -> Option<Duid<'a>> {
+        self.options.iter().find_map(|x| match x {
+            DhcpOption::ServerId(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// None means success (RFC 8415 21.13).
+    pub fn status_code(&self) -> Option<StatusCode<'a>> {
+        self.options.iter().find_map(|x| match x {
+            DhcpOption::StatusCode(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn ia_na(&self, iaid: u32) -> Option<IaNa<'a>> {
+        self.options.iter().find_map(|x| match x {
+            DhcpOption::IaNa(x) if x.iaid == iaid => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn has_rapid_commit(&self) -> bool {
+        self.options
+            .iter()
+            .any(|x| matches!(x, DhcpOption::RapidCommit))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum DhcpOption<'a> {
+    /// 1.
+    ClientId(Duid<'a>),
+    /// 2.
+    ServerId(Duid<'a>),
+    /// 3.
+    IaNa(IaNa<'a>),
+    /// 5 , inside IA_NA.
+    IaAddr(IaAddr<'a>),
+    /// 6.
+    OptionRequest(OptionRequest<'a>),
+    /// 8 , hundredths of a second since the first message of the exchange.
+    ElapsedTime(u16),
+    /// 13.
+    StatusCode(StatusCode<'a>),
+    /// 14.
+    RapidCommit,
+    /// 23 (RFC 3646).
+    DnsServers(Addresses<'a>),
+    /// 24 (RFC 3646).
+    DomainList(DomainList<'a>),
+    Unknown(u16, &'a [u8]),
+}
+
+impl<'a> DhcpOption<'a> {
+    pub fn code(&self) -> u16 {
+        match self {
+            DhcpOption::ClientId(_) => 1,
+            DhcpOption::ServerId(_) => 2,
+            DhcpOption::IaNa(_) => 3,
+            DhcpOption::IaAddr(_) => 5,
+            DhcpOption::OptionRequest(_) => 6,
+            DhcpOption::ElapsedTime(_) => 8,
+            DhcpOption::StatusCode(_) => 13,
+            DhcpOption::RapidCommit => 14,
+            DhcpOption::DnsServers(_) => 23,
+            DhcpOption::DomainList(_) => 24,
+            DhcpOption::Unknown(code, _) => *code,
+        }
+    }
+
+    /// None for a malformed option.
+    pub fn decode(code: u16, data: &'a [u8]) -> Option<Self> {
+        Some(match (code, data) {
+            (1, x) => DhcpOption::ClientId(Duid::decode(x)?),
+            (2, x) => DhcpOption::ServerId(Duid::decode(x)?),
+            (3, [a, b, c, d, e, f, g, h, i, j, k, l, options @ ..]) => DhcpOption::IaNa(IaNa {
+                iaid: u32::from_be_bytes([*a, *b, *c, *d]),
+                t1: u32::from_be_bytes([*e, *f, *g, *h]),
+                t2: u32::from_be_bytes([*i, *j, *k, *l]),
+                options: DhcpOptions::Bytes(options),
+            }),
+            (5, x) if x.len() >= 24 => {
+                let mut addr = [0; 16];
+                addr.copy_from_slice(&x[0..16]);
+                DhcpOption::IaAddr(IaAddr {
+                    addr,
+                    preferred_lifetime: u32::from_be_bytes([x[16], x[17], x[18], x[19]]),
+                    valid_lifetime: u32::from_be_bytes([x[20], x[21], x[22], x[23]]),
+                    options: DhcpOptions::Bytes(&x[24..]),
+                })
+            }
+            (6, x) if x.len() % 2 == 0 => DhcpOption::OptionRequest(OptionRequest::Bytes(x)),
+            (8, [a, b]) => DhcpOption::ElapsedTime(u16::from_be_bytes([*a, *b])),
+            (13, [a, b, message @ ..]) => DhcpOption::StatusCode(StatusCode {
+                code: u16::from_be_bytes([*a, *b]),
+                message: core::str::from_utf8(message).ok()?,
+            }),
+            (14, []) => DhcpOption::RapidCommit,
+            (23, x) if x.len() % 16 == 0 => DhcpOption::DnsServers(Addresses::Bytes(x)),
+            (24, x) if DomainList::is_valid(x) => DhcpOption::DomainList(DomainList::Bytes(x)),
+            (1..=3 | 5 | 6 | 8 | 13 | 14 | 23 | 24, _) => return None,
+            (code, x) => DhcpOption::Unknown(code, x),
+        })
+    }
+
+    /// write the value (without code and length) , returns the length.
+    fn encode_value(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            DhcpOption::ClientId(x) | DhcpOption::ServerId(x) => x.encode(buffer),
+            DhcpOption::IaNa(x) => {
+                let header = buffer.get_mut(..12)?;
+                header[0..4].copy_from_slice(&x.iaid.to_be_bytes());
+                header[4..8].copy_from_slice(&x.t1.to_be_bytes());
+                header[8..12].copy_from_slice(&x.t2.to_be_bytes());
+                Some(12 + x.options.encode(&mut buffer[12..])?)
+            }
+            DhcpOption::IaAddr(x) => {
+                let header = buffer.get_mut(..24)?;
+                header[0..16].copy_from_slice(&x.addr);
+                header[16..20].copy_from_slice(&x.preferred_lifetime.to_be_bytes());
+                header[20..24].copy_from_slice(&x.valid_lifetime.to_be_bytes());
+                Some(24 + x.options.encode(&mut buffer[24..])?)
+            }
+            DhcpOption::OptionRequest(x) => {
+                let mut len = 0;
+                for code in x.iter() {
+                    buffer
+                        .get_mut(len..len + 2)?
+                        .copy_from_slice(&code.to_be_bytes());
+                    len += 2;
+                }
+                Some(len)
+            }
+            DhcpOption::ElapsedTime(x) => {
+                buffer.get_mut(..2)?.copy_from_slice(&x.to_be_bytes());
+                Some(2)
+            }
+            DhcpOption::StatusCode(x) => {
+                let len = 2 + x.message.len();
+                let buffer = buffer.get_mut(..len)?;
+                buffer[0..2].copy_from_slice(&x.code.to_be_bytes());
+                buffer[2..].copy_from_slice(x.message.as_bytes());
+                Some(len)
+            }
+            DhcpOption::RapidCommit => Some(0),
+            DhcpOption::DnsServers(x) => {
+                let mut len = 0;
+                for addr in x.iter() {
+                    buffer.get_mut(len..len + 16)?.copy_from_slice(&addr);
+                    len += 16;
+                }
+                Some(len)
+            }
+            DhcpOption::DomainList(x) => x.encode(buffer),
+            DhcpOption::Unknown(_, x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                Some(x.len())
+            }
+        }
+    }
+}
+
+/// the options of a message , IA_NA or IA_ADDR.
+#[derive(Clone, Copy, Debug)]
+pub enum DhcpOptions<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    List(&'a [DhcpOption<'a>]),
+}
+
+impl<'a> DhcpOptions<'a> {
+    pub fn iter(&self) -> DhcpOptionsIter<'a> {
+        match self {
+            DhcpOptions::Bytes(x) => DhcpOptionsIter::Bytes(x),
+            DhcpOptions::List(x) => DhcpOptionsIter::List(x.iter()),
+        }
+    }
+
+    /// code , length and value of each option , returns the length.
+    fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        if let DhcpOptions::Bytes(x) = self {
+            buffer.get_mut(..x.len())?.copy_from_slice(x);
+            return Some(x.len());
+        }
+        let mut len = 0;
+        for option in self.iter() {
+            let value_len = option.encode_value(buffer.get_mut(len + 4..)?)?;
+            let header = &mut buffer[len..len + 4];
+            header[0..2].copy_from_slice(&option.code().to_be_bytes());
+            header[2..4].copy_from_slice(&u16::try_from(value_len).ok()?.to_be_bytes());
+            len += 4 + value_len;
+        }
+        Some(len)
+    }
+}
+
+pub enum DhcpOptionsIter<'a> {
+    Bytes(&'a [u8]),
+    List(core::slice::Iter<'a, DhcpOption<'a>>),
+}
+
+impl<'a> Iterator for DhcpOptionsIter<'a> {
+    type Item = DhcpOption<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            DhcpOptionsIter::Bytes(bytes) => loop {
+                let [a, b, c, d, rest @ ..] = *bytes else {
+                    *bytes = &[];
+                    return None;
+                };
+                let code = u16::from_be_bytes([*a, *b]);
+                let len = u16::from_be_bytes([*c, *d]) as usize;
+                let Some(data) = rest.get(..len) else {
+                    *bytes = &[];
+                    return None;
+                };
+                *bytes = &rest[len..];
+                if let Some(option) = DhcpOption::decode(code, data) {
+                    return Some(option);
+                }
+            },
+            DhcpOptionsIter::List(x) => x.next().copied(),
+        }
+    }
+}
+
+/// Identity Association for Non-temporary Addresses.
+#[derive(Clone, Copy, Debug)]
+pub struct IaNa<'a> {
+    pub iaid: u32,
+    pub t1: u32,
+    pub t2: u32,
+    /// IA_ADDR and Status Code.
+    pub options: DhcpOptions<'a>,
+}
+
+impl<'a> IaNa<'a> {
+    pub fn addrs(&self) -> impl Iterator<Item = IaAddr<'a>> {
+        self.options.iter().filter_map(|x| match x {
+            DhcpOption::IaAddr(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn status_code(&self) -> Option<StatusCode<'a>> {
+        self.options.iter().find_map(|x| match x {
+            DhcpOption::StatusCode(x) => Some(x),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IaAddr<'a> {
+    pub addr: [u8; 16],
+    pub preferred_lifetime: u32,
+    pub valid_lifetime: u32,
+    pub options: DhcpOptions<'a>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum OptionRequest<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    Codes(&'a [u16]),
+}
+
+impl<'a> OptionRequest<'a> {
+    pub fn iter(&self) -> OptionCodes<'a> {
+        match self {
+            OptionRequest::Bytes(x) => OptionCodes::Bytes(x.chunks_exact(2)),
+            OptionRequest::Codes(x) => OptionCodes::Codes(x.iter()),
+        }
+    }
+}
+
+pub enum OptionCodes<'a> {
+    Bytes(core::slice::ChunksExact<'a, u8>),
+    Codes(core::slice::Iter<'a, u16>),
+}
+
+impl Iterator for OptionCodes<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            OptionCodes::Bytes(x) => x.next().map(|x| u16::from_be_bytes([x[0], x[1]])),
+            OptionCodes::Codes(x) => x.next().copied(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StatusCode<'a> {
+    pub code: u16,
+    pub message: &'a str,
+}
+
+impl StatusCode<'_> {
+    pub const SUCCESS: u16 = 0;
+    pub const UNSPEC_FAIL: u16 = 1;
+    pub const NO_ADDRS_AVAIL: u16 = 2;
+    pub const NO_BINDING: u16 = 3;
+    pub const NOT_ON_LINK: u16 = 4;
+    pub const USE_MULTICAST: u16 = 5;
+    pub const NO_PREFIX_AVAIL: u16 = 6;
+}
+
+/// value of option 24 , fully qualified names without compression
+/// (RFC 8415 10).
+#[derive(Clone, Copy, Debug)]
+pub enum DomainList<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    Names(&'a [&'a str]),
+}
+
+impl<'a> DomainList<'a> {
+    pub fn iter(&self) -> DomainListIter<'a> {
+        match self {
+            DomainList::Bytes(x) => DomainListIter::Bytes(x),
+            DomainList::Names(x) => DomainListIter::Names(x.iter()),
+        }
+    }
+
+    /// names ending with the root label , a compression pointer is not one.
+    fn is_valid(mut bytes: &[u8]) -> bool {
+        while !bytes.is_empty() {
+            match split_name(bytes) {
+                Some((_, rest)) => bytes = rest,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// write the value (without code and length) , returns the length.
+    fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        let names = match self {
+            DomainList::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                return Some(x.len());
+            }
+            DomainList::Names(x) => x,
+        };
+        let mut len = 0;
+        for name in names.iter() {
+            // always fully qualified.
+            let name = name.trim_end_matches('.');
+            len += DomainName::Text(name).encode(buffer.get_mut(len..)?)?;
+            *buffer.get_mut(len)? = 0;
+            len += 1;
+        }
+        Some(len)
+    }
+}
+
+/// the first name , root label included , and the rest.
+fn split_name(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut len = 0;
+    loop {
+        match *bytes.get(len)? {
+            0 => return Some(bytes.split_at(len + 1)),
+            label_len @ 1..=63 => len += 1 + label_len as usize,
+            _ => return None,
+        }
+    }
+}
+
+pub enum DomainListIter<'a> {
+    Bytes(&'a [u8]),
+    Names(core::slice::Iter<'a, &'a str>),
+}
+
+impl<'a> Iterator for DomainListIter<'a> {
+    type Item = DomainName<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            DomainListIter::Bytes(bytes) => {
+                let Some((name, rest)) = split_name(bytes) else {
+                    *bytes = &[];
+                    return None;
+                };
+                *bytes = rest;
+                Some(DomainName::Wire(name))
+            }
+            DomainListIter::Names(x) => x.next().map(|x| DomainName::Text(x)),
+        }
+    }
+}
+
+/// IPv6 addresses.
+#[derive(Clone, Copy, Debug)]
+pub enum Addresses<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    Addrs(&'a [[u8; 16]]),
+}
+
+impl<'a> Addresses<'a> {
+    pub fn iter(&self) -> AddressesIter<'a> {
+        match self {
+            Addresses::Bytes(x) => AddressesIter::Bytes(x.chunks_exact(16)),
+            Addresses::Addrs(x) => AddressesIter::Addrs(x.iter()),
+        }
+    }
+}
+
+pub enum AddressesIter<'a> {
+    Bytes(core::slice::ChunksExact<'a, u8>),
+    Addrs(core::slice::Iter<'a, [u8; 16]>),
+}
+
+impl Iterator for AddressesIter<'_> {
+    type Item = [u8; 16];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            AddressesIter::Bytes(x) => x.next().and_then(|x| x.try_into().ok()),
+            AddressesIter::Addrs(x) => x.next().copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUID: Duid = Duid::Ll {
+        hw_type: 1,
+        link_layer_addr: &[2, 0, 0, 0, 0, 1],
+    };
+    const ADDR: [u8; 16] = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5];
+    const DNS: [u8; 16] = [
+        0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x53,
+    ];
+
+    fn encode_reply(buffer: &mut [u8]) -> usize {
+        let addr_options = [DhcpOption::StatusCode(StatusCode {
+            code: StatusCode::SUCCESS,
+            message: "ok",
+        })];
+        let ia_options = [DhcpOption::IaAddr(IaAddr {
+            addr: ADDR,
+            preferred_lifetime: 100,
+            valid_lifetime: 200,
+            options: DhcpOptions::List(&addr_options),
+        })];
+        let options = [
+            DhcpOption::ClientId(DUID),
+            DhcpOption::ServerId(Duid::Uuid([7; 16])),
+            DhcpOption::IaNa(IaNa {
+                iaid: 1,
+                t1: 50,
+                t2: 80,
+                options: DhcpOptions::List(&ia_options),
+            }),
+            DhcpOption::RapidCommit,
+            DhcpOption::DnsServers(Addresses::Addrs(&[DNS])),
+            DhcpOption::DomainList(DomainList::Names(&["example.com", "lab.example.com."])),
+        ];
+        Message::new(MessageType::Reply, 0x12345678, &options)
+            .encode(buffer)
+            .unwrap()
+    }
+
+    #[test]
+    fn solicit() {
+        let options = [
+            DhcpOption::ClientId(DUID),
+            DhcpOption::IaNa(IaNa {
+                iaid: 1,
+                t1: 0,
+                t2: 0,
+                options: DhcpOptions::List(&[]),
+            }),
+            DhcpOption::OptionRequest(OptionRequest::Codes(&[23, 24])),
+            DhcpOption::ElapsedTime(0),
+            DhcpOption::RapidCommit,
+        ];
+        let mut buffer = [0; 128];
+        let len = Message::new(MessageType::Solicit, 0xabcdef, &options)
+            .encode(&mut buffer)
+            .unwrap();
+        assert_eq!(
+            &buffer[..len],
+            &[
+                1, 0xab, 0xcd, 0xef, // header
+                0, 1, 0, 10, 0, 3, 0, 1, 2, 0, 0, 0, 0, 1, // client id
+                0, 3, 0, 12, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, // IA_NA
+                0, 6, 0, 4, 0, 23, 0, 24, // ORO
+                0, 8, 0, 2, 0, 0, // elapsed time
+                0, 14, 0, 0, // rapid commit
+            ]
+        );
+        let message = Message::decode(&buffer[..len]).unwrap();
+        assert_eq!(message.message_type, MessageType::Solicit);
+        let codes = message.options.iter().find_map(|x| match x {
+            DhcpOption::OptionRequest(x) => Some(x),
+            _ => None,
+        });
+        assert!(codes.unwrap().iter().eq([23, 24]));
+    }
+
+    #[test]
+    fn reply_round_trip() {
+        let mut buffer = [0; 256];
+        let len = encode_reply(&mut buffer);
+        let message = Message::decode(&buffer[..len]).unwrap();
+        assert_eq!(message.message_type, MessageType::Reply);
+        assert_eq!(message.transaction_id, 0x345678);
+        assert!(matches!(
+            message.client_id(),
+            Some(Duid::Ll {
+                hw_type: 1,
+                link_layer_addr: [2, 0, 0, 0, 0, 1]
+            })
+        ));
+        assert!(matches!(message.server_id(), Some(Duid::Uuid(x)) if x == [7; 16]));
+        assert!(message.has_rapid_commit());
+        assert!(message.status_code().is_none());
+
+        let ia_na = message.ia_na(1).unwrap();
+        assert_eq!((ia_na.t1, ia_na.t2), (50, 80));
+        assert!(message.ia_na(2).is_none());
+        let addr = ia_na.addrs().next().unwrap();
+        assert_eq!(addr.addr, ADDR);
+        assert_eq!((addr.preferred_lifetime, addr.valid_lifetime), (100, 200));
+        let status = addr
+            .options
+            .iter()
+            .find_map(|x| match x {
+                DhcpOption::StatusCode(x) => Some(x),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!((status.code, status.message), (StatusCode::SUCCESS, "ok"));
+
+        for option in message.options.iter() {
+            match option {
+                DhcpOption::DnsServers(x) => assert!(x.iter().eq([DNS])),
+                DhcpOption::DomainList(x) => {
+                    let mut names = x.iter();
+                    let mut text = [0; 32];
+                    let name = names.next().unwrap();
+                    assert_eq!(name.to_text(&mut text), Some("example.com."));
+                    let name = names.next().unwrap();
+                    assert_eq!(name.to_text(&mut text), Some("lab.example.com."));
+                    assert!(names.next().is_none());
+                }
+                _ => {}
+            }
+        }
+
+        // what was decoded encodes the same.
+        let mut again = [0; 256];
+        let again_len = message.encode(&mut again).unwrap();
+        assert_eq!(&again[..again_len], &buffer[..len]);
+    }
+
+    #[test]
+    fn domain_list_is_not_compressed() {
+        let mut buffer = [0; 256];
+        let len = encode_reply(&mut buffer);
+        let list = &buffer[len - 30..len];
+        assert_eq!(&list[..13], b"\x07example\x03com\x00");
+        assert_eq!(&list[13..], b"\x03lab\x07example\x03com\x00");
+
+        // a pointer to the first name.
+        let compressed = b"\x07example\x03com\x00\x03lab\xc0\x00";
+        assert!(DhcpOption::decode(24, compressed).is_none());
+        // no root label.
+        assert!(DhcpOption::decode(24, b"\x07example\x03com").is_none());
+        assert!(DhcpOption::decode(24, b"").is_some());
+    }
+
+    #[test]
+    fn malformed_messages() {
+        let mut buffer = [0; 256];
+        let len = encode_reply(&mut buffer);
+        for len in 0..len {
+            if let Some(message) = Message::decode(&buffer[..len]) {
+                for option in message.options.iter() {
+                    if let DhcpOption::IaNa(x) = option {
+                        x.addrs().for_each(|x| x.options.iter().for_each(|_| ()));
+                    }
+                }
+            }
+        }
+        // relay messages are not supported.
+        assert!(Message::decode(&[12, 0, 0, 0]).is_none());
+        assert!(DhcpOption::decode(8, &[0]).is_none());
+        assert!(DhcpOption::decode(23, &[0; 15]).is_none());
+        assert!(DhcpOption::decode(14, &[0]).is_none());
+        assert!(matches!(
+            DhcpOption::decode(99, &[1]),
+            Some(DhcpOption::Unknown(99, [1]))
+        ));
+    }
+
+    #[test]
+    fn encode_in_short_buffer() {
+        let mut buffer = [0; 256];
+        let len = encode_reply(&mut buffer);
+        let message = Message::decode(&buffer[..len]).unwrap();
+        for short in 0..len {
+            let mut buffer = [0; 256];
+            assert!(message.encode(&mut buffer[..short]).is_none());
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! domain names in DNS wire format (RFC 1035 3.1) , Client FQDN (RFC 4702)
+//! and Domain Search (RFC 3397).
+
+/// a domain name.
+///
+/// text ending with '.' is fully qualified , otherwise it is a partial name
+/// and is sent without the root label.
+#[derive(Clone, Copy, Debug)]
+pub enum DomainName<'a> {
+    /// wire format as received.
+    Wire(&'a [u8]),
+    /// dotted text.
+    Text(&'a str),
+    /// wire format starting at the offset , compression pointers are relative
+    /// to the start of the bytes.
+    Compressed(&'a [u8], usize),
+}
+
+impl<'a> DomainName<'a> {
+    pub fn labels(&self) -> Labels<'a> {
+        match self {
+            DomainName::Wire(x) => Labels::Wire(x),
+            DomainName::Text(x) => Labels::Text(x.as_bytes()),
+            DomainName::Compressed(bytes, start) => Labels::Compressed {
+                bytes,
+                pos: *start,
+                segment: *start,
+            },
+        }
+    }
+
+    /// same labels , ignoring ASCII case.
+    pub fn eq_ignore_ascii_case(&self, other: &DomainName) -> bool {
+        let mut labels = self.labels();
+        let mut other_labels = other.labels();
+        loop {
+            match (labels.next(), other_labels.next()) {
+                (Some(x), Some(y)) if x.eq_ignore_ascii_case(y) => {}
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+
+    pub fn is_fully_qualified(&self) -> bool {
+        match self {
+            DomainName::Wire(x) => {
+                let mut bytes = *x;
+                while let Some((&len, rest)) = bytes.split_first() {
+                    if len == 0 {
+                        return true;
+                    }
+                    bytes = rest.get(len as usize..).unwrap_or(&[]);
+                }
+                false
+            }
+            DomainName::Text(x) => x.ends_with('.'),
+            // names of a search list always end with the root label.
+            DomainName::Compressed(..) => true,
+        }
+    }
+
+    /// dotted text into `buffer` , None if it does not fit or is not UTF-8.
+    pub fn to_text<'b>(&self, buffer: &'b mut [u8]) -> Option<&'b str> {
+        let mut len = 0;
+        for label in self.labels() {
+            if len != 0 {
+                *buffer.get_mut(len)? = b'.';
+                len += 1;
+            }
+            buffer
+                .get_mut(len..len + label.len())?
+                .copy_from_slice(label);
+            len += label.len();
+        }
+        if self.is_fully_qualified() {
+            *buffer.get_mut(len)? = b'.';
+            len += 1;
+        }
+        core::str::from_utf8(&buffer[0..len]).ok()
+    }
+
+    /// write in wire format , returns the length.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        let mut len = 0;
+        for label in self.labels() {
+            if label.is_empty() || label.len() > 63 {
+                return None;
+            }
+            *buffer.get_mut(len)? = label.len() as u8;
+            buffer
+                .get_mut(len + 1..len + 1 + label.len())?
+                .copy_from_slice(label);
+            len += 1 + label.len();
+        }
+        if self.is_fully_qualified() {
+            *buffer.get_mut(len)? = 0;
+            len += 1;
+        }
+        (len <= 255).then_some(len)
+    }
+}
+
+/// labels of a name , the root label is not included.
+pub enum Labels<'a> {
+    Wire(&'a [u8]),
+    Text(&'a [u8]),
+    /// `segment` is where the labels being read start. a pointer must go
+    /// before it , so the walk always ends.
+    Compressed {
+        bytes: &'a [u8],
+        pos: usize,
+        segment: usize,
+    },
+}
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Labels::Wire(bytes) => {
+                let (&len, rest) = bytes.split_first()?;
+                // root label , or a malformed one.
+                let label = rest.get(..len as usize).filter(|_| len != 0 && len <= 63);
+                *bytes = label.map_or(&[], |x| &rest[x.len()..]);
+                label
+            }
+            Labels::Text(bytes) => {
+                if bytes.is_empty() {
+                    return None;
+                }
+                let len = bytes.iter().position(|x| *x == b'.').unwrap_or(bytes.len());
+                let label = &bytes[0..len];
+                *bytes = bytes.get(len + 1..).unwrap_or(&[]);
+                Some(label)
+            }
+            Labels::Compressed {
+                bytes,
+                pos,
+                segment,
+            } => loop {
+                let len = *bytes.get(*pos)?;
+                match len {
+                    // root label.
+                    0 => return None,
+                    1..=63 => {
+                        let label = bytes.get(*pos + 1..*pos + 1 + len as usize);
+                        *pos = label.map_or(bytes.len(), |x| *pos + 1 + x.len());
+                        return label;
+                    }
+                    0xc0..=0xff => {
+                        let low = *bytes.get(*pos + 1)?;
+                        let target = u16::from_be_bytes([len & 0x3f, low]) as usize;
+                        if target >= *segment {
+                            *pos = bytes.len();
+                            return None;
+                        }
+                        *pos = target;
+                        *segment = target;
+                    }
+                    // reserved label types.
+                    _ => return None,
+                }
+            },
+        }
+    }
+}
+
+/// value of option 119.
+#[derive(Clone, Copy, Debug)]
+pub enum DomainSearch<'a> {
+    /// as received , all instances of the option concatenated.
+    Bytes(&'a [u8]),
+    /// to be sent , compressed when encoded.
+    Names(&'a [&'a str]),
+}
+
+impl<'a> DomainSearch<'a> {
+    pub fn iter(&self) -> DomainSearchIter<'a> {
+        match self {
+            DomainSearch::Bytes(x) => DomainSearchIter::Bytes { bytes: x, pos: 0 },
+            DomainSearch::Names(x) => DomainSearchIter::Names(x.iter()),
+        }
+    }
+
+    /// write the value (without code and length) , returns the length.
+    ///
+    /// a name , or its end , already written is replaced by a pointer.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        let names = match self {
+            DomainSearch::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                return Some(x.len());
+            }
+            DomainSearch::Names(x) => x,
+        };
+        let mut len = 0;
+        for name in names.iter() {
+            let name_start = len;
+            let mut rest = name.trim_end_matches('.');
+            loop {
+                if rest.is_empty() {
+                    *buffer.get_mut(len)? = 0;
+                    len += 1;
+                    break;
+                }
+                let suffix = DomainName::Text(rest);
+                // only earlier names are complete.
+                let written = &buffer[..len];
+                let pointer = (0..name_start.min(0x4000))
+                    .find(|x| DomainName::Compressed(written, *x).eq_ignore_ascii_case(&suffix));
+                if let Some(pointer) = pointer {
+                    buffer
+                        .get_mut(len..len + 2)?
+                        .copy_from_slice(&(0xc000 | pointer as u16).to_be_bytes());
+                    len += 2;
+                    break;
+                }
+                let (label, next) = rest.split_once('.').unwrap_or((rest, ""));
+                if label.is_empty() || label.len() > 63 {
+                    return None;
+                }
+                *buffer.get_mut(len)? = label.len() as u8;
+                buffer
+                    .get_mut(len + 1..len + 1 + label.len())?
+                    .copy_from_slice(label.as_bytes());
+                len += 1 + label.len();
+                rest = next;
+            }
+        }
+        Some(len)
+    }
+}
+
+pub enum DomainSearchIter<'a> {
+    Bytes { bytes: &'a [u8], pos: usize },
+    Names(core::slice::Iter<'a, &'a str>),
+}
+
+impl<'a> Iterator for DomainSearchIter<'a> {
+    type Item = DomainName<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            DomainSearchIter::Bytes { bytes, pos } => {
+                let start = *pos;
+                // skip to the end of this name , a root label or a pointer.
+                loop {
+                    match *bytes.get(*pos)? {
+                        0 => {
+                            *pos += 1;
+                            break;
+                        }
+                        len @ 1..=63 => *pos += 1 + len as usize,
+                        0xc0..=0xff if *pos + 1 < bytes.len() => {
+                            *pos += 2;
+                            break;
+                        }
+                        _ => {
+                            *pos = bytes.len();
+                            return None;
+                        }
+                    }
+                }
+                Some(DomainName::Compressed(bytes, start))
+            }
+            DomainSearchIter::Names(x) => x.next().map(|x| DomainName::Text(x)),
+        }
+    }
+}
+
+/// value of option 81.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientFqdn<'a> {
+    /// S : the server should do the A record update. in a reply , it will.
+    pub server_update: bool,
+    /// O : the server did not follow the S flag of the client.
+    pub overridden: bool,
+    /// N : the server should do no update at all.
+    pub no_update: bool,
+    /// deprecated , 0 from clients and 255 from servers.
+    pub rcode1: u8,
+    pub rcode2: u8,
+    pub domain_name: DomainName<'a>,
+}
+
+impl<'a> ClientFqdn<'a> {
+    const S: u8 = 0x01;
+    const O: u8 = 0x02;
+    const E: u8 = 0x04;
+    const N: u8 = 0x08;
+
+    /// ask the server to update the A record for `domain_name`.
+    pub fn new(domain_name: &'a str) -> Self {
+        Self {
+            server_update: true,
+            overridden: false,
+            no_update: false,
+            rcode1: 0,
+            rcode2: 0,
+            domain_name: DomainName::Text(domain_name),
+        }
+    }
+
+    /// in a reply , does the server update the A record ?
+    pub fn server_updates_a_record(&self) -> bool {
+        self.server_update && !self.no_update
+    }
+
+    pub(crate) fn decode(bytes: &'a [u8]) -> Option<Self> {
+        let [flags, rcode1, rcode2, name @ ..] = bytes else {
+            return None;
+        };
+        Some(Self {
+            server_update: flags & Self::S != 0,
+            overridden: flags & Self::O != 0,
+            no_update: flags & Self::N != 0,
+            rcode1: *rcode1,
+            rcode2: *rcode2,
+            domain_name: if flags & Self::E != 0 {
+                DomainName::Wire(name)
+            } else {
+                // deprecated ASCII encoding.
+                DomainName::Text(core::str::from_utf8(name).ok()?)
+            },
+        })
+    }
+
+    /// write the value (without code and length) , returns the length.
+    ///
+    /// the name is always sent in wire format.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        let mut flags = Self::E;
+        if self.server_update {
+            flags |= Self::S;
+        }
+        if self.overridden {
+            flags |= Self::O;
+        }
+        if self.no_update {
+            flags |= Self::N;
+        }
+        buffer
+            .get_mut(0..3)?
+            .copy_from_slice(&[flags, self.rcode1, self.rcode2]);
+        Some(3 + self.domain_name.encode(buffer.get_mut(3..)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the example of RFC 3397 4.
+    const SEARCH_LIST: &[u8] = b"\x03eng\x05apple\x03com\x00\x09marketing\xc0\x04";
+
+    #[test]
+    fn compress_search_list() {
+        let names = ["eng.apple.com", "marketing.apple.com."];
+        let mut buffer = [0; 64];
+        let len = DomainSearch::Names(&names).encode(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], SEARCH_LIST);
+
+        let mut text = [0; 32];
+        let mut decoded = DomainSearch::Bytes(&buffer[..len]).iter();
+        let name = decoded.next().unwrap();
+        assert_eq!(name.to_text(&mut text), Some("eng.apple.com."));
+        let name = decoded.next().unwrap();
+        assert_eq!(name.to_text(&mut text), Some("marketing.apple.com."));
+        assert!(decoded.next().is_none());
+    }
+
+    #[test]
+    fn same_name_ignoring_case() {
+        let name = DomainSearch::Bytes(SEARCH_LIST).iter().nth(1).unwrap();
+        assert!(name.eq_ignore_ascii_case(&DomainName::Text("Marketing.APPLE.com")));
+        assert!(!name.eq_ignore_ascii_case(&DomainName::Text("marketing.apple")));
+    }
+
+    #[test]
+    fn pointer_loops() {
+        let mut text = [0; 64];
+        // to itself , forward , and between two names.
+        for bytes in [
+            &b"\xc0\x00"[..],
+            b"\x03abc\xc0\x06\x03def\x00",
+            b"\x03abc\xc0\x06\x03def\xc0\x00",
+            b"\x03abc\x00\x03def\xc0\x05",
+        ] {
+            for name in DomainSearch::Bytes(bytes).iter() {
+                assert!(name.labels().count() <= 2);
+                name.to_text(&mut text);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_names() {
+        let mut buffer = [0; 300];
+        let long = core::str::from_utf8(&[b'x'; 64]).unwrap();
+        assert!(DomainName::Text(long).encode(&mut buffer).is_none());
+        assert!(DomainName::Text("a..b").encode(&mut buffer).is_none());
+        for len in 0..SEARCH_LIST.len() {
+            DomainSearch::Bytes(&SEARCH_LIST[..len])
+                .iter()
+                .for_each(|x| x.labels().for_each(|_| ()));
+        }
+    }
+
+    #[test]
+    fn client_fqdn() {
+        let mut buffer = [0; 32];
+        let len = ClientFqdn::new("host.lan.").encode(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"\x05\x00\x00\x04host\x03lan\x00");
+        let decoded = ClientFqdn::decode(&buffer[..len]).unwrap();
+        assert!(decoded.server_updates_a_record());
+        let mut text = [0; 16];
+        assert_eq!(decoded.domain_name.to_text(&mut text), Some("host.lan."));
+        // partial name , without the root label.
+        let len = ClientFqdn::new("host").encode(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"\x05\x00\x00\x04host");
+        assert!(ClientFqdn::decode(&[0x01, 0]).is_none());
+    }
+}
@@ -0,0 +1,105 @@
+//! the loop every network driver runs around [`Client`].
+//!
+//! it restarts discovery when no server answers , runs the renewal timers and
+//! turns state changes into [`Event`]s. time is seconds , as for [`Client`].
+use crate::client::{Client, Event, State};
+use crate::server::Destination;
+use crate::DHCPMessage;
+
+/// how long to wait for OFFER or ACK before starting again.
+const TIMEOUT: u32 = 10;
+
+/// what to do after [`Driver::receive`] or [`Driver::poll`].
+pub(crate) struct Output<'a> {
+    pub(crate) send: Option<(DHCPMessage<'a>, Destination)>,
+    pub(crate) event: Option<Event>,
+}
+
+pub(crate) struct Driver<'a> {
+    pub(crate) client: Client<'a>,
+    /// xorshift state for transaction ids.
+    seed: u32,
+    retry_at: u32,
+}
+
+impl<'a> Driver<'a> {
+    pub(crate) fn new(client: Client<'a>, seed: u32) -> Self {
+        Self {
+            client,
+            seed: seed.max(1),
+            retry_at: 0,
+        }
+    }
+
+    /// when [`Driver::poll`] has something to do , None when bound for good.
+    pub(crate) fn poll_at(&self) -> Option<u32> {
+        match self.client.state() {
+            State::Init => Some(0),
+            State::Selecting | State::Requesting => Some(self.retry_at),
+            State::Bound | State::Renewing | State::Rebinding => self.client.poll_at(),
+        }
+    }
+
+    /// process a received datagram , `buffer` is for decoding.
+    pub(crate) fn receive(&mut self, packet: &[u8], buffer: &mut [u8], now: u32) -> Output<'a> {
+        let state = self.client.state();
+        let reply = match DHCPMessage::decode(packet, buffer) {
+            Some(message) => self.client.handle(&message, now),
+            None => None,
+        };
+        if reply.is_some() {
+            self.retry_at = now.saturating_add(TIMEOUT);
+        }
+        Output {
+            send: reply.map(|x| (x, Destination::Broadcast)),
+            event: self.event(state),
+        }
+    }
+
+    /// send what is due at `now`.
+    pub(crate) fn poll(&mut self, now: u32) -> Output<'a> {
+        let state = self.client.state();
+        let send = match state {
+            State::Init => Some(self.discover(now)),
+            State::Selecting | State::Requesting if now >= self.retry_at => {
+                Some(self.discover(now))
+            }
+            State::Bound | State::Renewing | State::Rebinding => self
+                .client
+                .poll(now)
+                .map(|x| (x, self.client.destination())),
+            _ => None,
+        };
+        Output {
+            send,
+            event: self.event(state),
+        }
+    }
+
+    /// give the lease back.
+    pub(crate) fn release(&mut self) -> Output<'a> {
+        let state = self.client.state();
+        Output {
+            send: self.client.release(),
+            event: self.event(state),
+        }
+    }
+
+    fn discover(&mut self, now: u32) -> (DHCPMessage<'a>, Destination) {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.retry_at = now.saturating_add(TIMEOUT);
+        (self.client.discover(self.seed), Destination::Broadcast)
+    }
+
+    /// what changed since `state`.
+    fn event(&self, state: State) -> Option<Event> {
+        let bound = |x| matches!(x, State::Bound | State::Renewing | State::Rebinding);
+        match (self.client.state(), self.client.lease()) {
+            (State::Bound, Some(lease)) if state != State::Bound => Some(Event::Configured(*lease)),
+            (x, _) if bound(state) && !bound(x) => Some(Event::Deconfigured),
+            _ => None,
+        }
+    }
+}
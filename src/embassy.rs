@@ -0,0 +1,156 @@
+//! async task with embassy-time timers , no allocator needed.
+//!
+//! lease changes are published through an embassy-sync [`Signal`] , so other
+//! tasks can wait for the network to be configured. the socket is anything
+//! implementing [`UdpSocket`] , e.g. an embassy-net UDP socket bound to port
+//! 68.
+use core::future::Future;
+
+use ::embassy_futures::select::{select, Either};
+use ::embassy_net::udp;
+use ::embassy_net::Ipv4Address;
+use ::embassy_sync::blocking_mutex::raw::RawMutex;
+use ::embassy_sync::signal::Signal;
+use ::embassy_time::{Instant, Timer};
+
+use crate::client::{Client, Event};
+use crate::server::Destination;
+use crate::transport::{AsyncDhcpTransport, DhcpClient};
+
+/// a UDP socket bound to the client port.
+pub trait UdpSocket {
+    type Error;
+
+    fn send_to(
+        &mut self,
+        buffer: &[u8],
+        addr: [u8; 4],
+        port: u16,
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// receive one datagram , returns its length.
+    fn recv_from(&mut self, buffer: &mut [u8]) -> impl Future<Output = Result<usize, Self::Error>>;
+}
+
+/// what an embassy-net UDP socket may fail with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UdpError {
+    Send(udp::SendError),
+    /// a truncated datagram is skipped , not reported.
+    Recv(udp::RecvError),
+}
+
+impl From<udp::SendError> for UdpError {
+    fn from(error: udp::SendError) -> Self {
+        UdpError::Send(error)
+    }
+}
+
+impl From<udp::RecvError> for UdpError {
+    fn from(error: udp::RecvError) -> Self {
+        UdpError::Recv(error)
+    }
+}
+
+impl UdpSocket for udp::UdpSocket<'_> {
+    type Error = UdpError;
+
+    async fn send_to(
+        &mut self,
+        buffer: &[u8],
+        addr: [u8; 4],
+        port: u16,
+    ) -> Result<(), Self::Error> {
+        udp::UdpSocket::send_to(self, buffer, (Ipv4Address::from(addr), port)).await?;
+        Ok(())
+    }
+
+    async fn recv_from(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            match udp::UdpSocket::recv_from(self, buffer).await {
+                Ok((len, _)) => return Ok(len),
+                // larger than any reply we asked for , wait for the next one.
+                Err(udp::RecvError::Truncated) => continue,
+            }
+        }
+    }
+}
+
+/// [`AsyncDhcpTransport`] over a [`UdpSocket`] , embassy-time tells the time.
+pub struct EmbassyTransport<'s, S>(pub &'s mut S);
+
+impl<S: UdpSocket> AsyncDhcpTransport for EmbassyTransport<'_, S> {
+    type Error = S::Error;
+
+    async fn send(&mut self, destination: Destination, buffer: &[u8]) -> Result<(), S::Error> {
+        let addr = match destination {
+            Destination::Relay(x) | Destination::Unicast(x) | Destination::UnicastToHardware(x) => {
+                x
+            }
+            Destination::Broadcast => [255; 4],
+        };
+        self.0.send_to(buffer, addr, 67).await
+    }
+
+    /// the source address is not known , always 0.0.0.0.
+    async fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        deadline: Option<u32>,
+    ) -> Result<Option<(usize, [u8; 4])>, S::Error> {
+        let at = deadline.map_or(Instant::MAX, |x| Instant::from_secs(x as u64));
+        match select(self.0.recv_from(buffer), Timer::at(at)).await {
+            Either::First(len) => Ok(Some((len?, [0; 4]))),
+            Either::Second(()) => Ok(None),
+        }
+    }
+
+    /// seconds since boot.
+    fn now(&self) -> u32 {
+        Instant::now().as_secs() as u32
+    }
+}
+
+/// [`DhcpClient`] publishing on a [`Signal`].
+pub struct EmbassyDhcpClient<'a> {
+    dhcp: DhcpClient<'a>,
+}
+
+impl<'a> EmbassyDhcpClient<'a> {
+    /// `seed` makes the transaction ids , e.g. from a hardware RNG.
+    pub fn new(client: Client<'a>, seed: u32) -> Self {
+        Self {
+            dhcp: DhcpClient::new(client, seed),
+        }
+    }
+
+    pub fn client(&self) -> &Client<'a> {
+        self.dhcp.client()
+    }
+
+    /// publish every lease change on `signal` , returns only on error.
+    ///
+    /// apply the [`Lease`](crate::client::Lease) to the stack , e.g. with
+    /// `embassy_net::Stack::set_config_v4`.
+    pub async fn run<S: UdpSocket, M: RawMutex>(
+        &mut self,
+        socket: &mut S,
+        signal: &Signal<M, Event>,
+    ) -> Result<(), S::Error> {
+        loop {
+            signal.signal(self.next_event(socket).await?);
+        }
+    }
+
+    /// wait until the lease changes.
+    pub async fn next_event<S: UdpSocket>(&mut self, socket: &mut S) -> Result<Event, S::Error> {
+        self.dhcp
+            .next_event_async(&mut EmbassyTransport(socket))
+            .await
+    }
+
+    /// send RELEASE for the lease , if any.
+    pub async fn release<S: UdpSocket>(&mut self, socket: &mut S) -> Result<(), S::Error> {
+        self.dhcp.release_async(&mut EmbassyTransport(socket)).await
+    }
+}
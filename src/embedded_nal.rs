@@ -0,0 +1,118 @@
+//! drive [`Client`] over any embedded-nal [`UdpFullStack`] , e.g. a network
+//! co-processor.
+//!
+//! call [`NalClient::poll`] often , it never blocks. the stack has to be able
+//! to send broadcasts from 0.0.0.0 before it has an address. apply the
+//! [`Lease`](crate::client::Lease) of [`Event::Configured`] to the stack.
+use core::net::{SocketAddr, SocketAddrV4};
+
+use ::embedded_nal::nb;
+use ::embedded_nal::UdpFullStack;
+
+use crate::client::{Client, Event};
+use crate::server::Destination;
+use crate::transport::{DhcpClient, DhcpTransport};
+
+/// [`DhcpTransport`] over a socket of a stack , never blocks.
+pub struct NalTransport<'s, S: UdpFullStack> {
+    pub stack: &'s mut S,
+    pub socket: &'s mut S::UdpSocket,
+    /// seconds , the stack does not tell the time.
+    pub now: u32,
+}
+
+impl<S: UdpFullStack> DhcpTransport for NalTransport<'_, S> {
+    type Error = S::Error;
+
+    /// dropped when the stack would block , retransmission takes care of it.
+    fn send(&mut self, destination: Destination, buffer: &[u8]) -> Result<(), S::Error> {
+        let addr = match destination {
+            Destination::Relay(x) | Destination::Unicast(x) | Destination::UnicastToHardware(x) => {
+                x
+            }
+            Destination::Broadcast => [255; 4],
+        };
+        let remote = SocketAddr::V4(SocketAddrV4::new(addr.into(), 67));
+        match self.stack.send_to(self.socket, remote, buffer) {
+            Ok(()) | Err(nb::Error::WouldBlock) => Ok(()),
+            Err(nb::Error::Other(e)) => Err(e),
+        }
+    }
+
+    fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        _deadline: Option<u32>,
+    ) -> Result<Option<(usize, [u8; 4])>, S::Error> {
+        match self.stack.receive(self.socket, buffer) {
+            Ok((len, SocketAddr::V4(addr))) => Ok(Some((len, addr.ip().octets()))),
+            Ok((len, SocketAddr::V6(_))) => Ok(Some((len, [0; 4]))),
+            Err(nb::Error::WouldBlock) => Ok(None),
+            Err(nb::Error::Other(e)) => Err(e),
+        }
+    }
+
+    fn now(&self) -> u32 {
+        self.now
+    }
+}
+
+/// [`DhcpClient`] with its socket.
+pub struct NalClient<'a, S: UdpFullStack> {
+    dhcp: DhcpClient<'a>,
+    socket: S::UdpSocket,
+}
+
+impl<'a, S: UdpFullStack> NalClient<'a, S> {
+    /// open a socket of `stack` bound to port 68.
+    ///
+    /// `seed` makes the transaction ids , e.g. from a hardware RNG.
+    pub fn new(stack: &mut S, client: Client<'a>, seed: u32) -> Result<Self, S::Error> {
+        let mut socket = stack.socket()?;
+        if let Err(e) = stack.bind(&mut socket, 68) {
+            stack.close(socket).ok();
+            return Err(e);
+        }
+        Ok(Self {
+            dhcp: DhcpClient::new(client, seed),
+            socket,
+        })
+    }
+
+    pub fn client(&self) -> &Client<'a> {
+        self.dhcp.client()
+    }
+
+    /// when [`NalClient::poll`] has something to do , None when bound for
+    /// good. seconds , as `now`.
+    pub fn poll_at(&self) -> Option<u32> {
+        self.dhcp.poll_at()
+    }
+
+    /// receive replies and send what is due at `now` , in seconds.
+    ///
+    /// WouldBlock when the lease did not change.
+    pub fn poll(&mut self, stack: &mut S, now: u32) -> nb::Result<Event, S::Error> {
+        let mut transport = NalTransport {
+            stack,
+            socket: &mut self.socket,
+            now,
+        };
+        self.dhcp.poll(&mut transport)?.ok_or(nb::Error::WouldBlock)
+    }
+
+    /// send RELEASE for the lease , if any.
+    pub fn release(&mut self, stack: &mut S, now: u32) -> Result<(), S::Error> {
+        let mut transport = NalTransport {
+            stack,
+            socket: &mut self.socket,
+            now,
+        };
+        self.dhcp.release(&mut transport)
+    }
+
+    /// close the socket.
+    pub fn close(self, stack: &mut S) -> Result<(), S::Error> {
+        stack.close(self.socket)
+    }
+}
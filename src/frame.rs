@@ -0,0 +1,254 @@
+//! Ethernet , IPv4 and UDP headers around a message , for drivers without an
+//! IP stack.
+//!
+//! a client without an address sends from 0.0.0.0 to 255.255.255.255 and must
+//! take unicast replies to the offered address , so frames are filtered by
+//! destination MAC address and port only. no VLAN tag , IP options are
+//! skipped , fragments are dropped.
+use crate::DHCPMessage;
+
+pub const ETHERNET_HEADER_LEN: usize = 14;
+pub const IPV4_HEADER_LEN: usize = 20;
+pub const UDP_HEADER_LEN: usize = 8;
+/// where the message starts in a frame we build.
+pub const FRAME_HEADER_LEN: usize = ETHERNET_HEADER_LEN + IPV4_HEADER_LEN + UDP_HEADER_LEN;
+
+const ETHERTYPE_IPV4: [u8; 2] = [0x08, 0x00];
+const PROTOCOL_UDP: u8 = 17;
+const TTL: u8 = 64;
+
+/// addresses and ports of a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub src_mac: [u8; 6],
+    pub dst_mac: [u8; 6],
+    pub src_ip_addr: [u8; 4],
+    pub dst_ip_addr: [u8; 4],
+    pub src_port: u16,
+    pub dst_port: u16,
+}
+
+impl FrameHeader {
+    /// from a client without an address to every server.
+    pub fn broadcast(src_mac: [u8; 6]) -> Self {
+        Self {
+            src_mac,
+            dst_mac: [0xff; 6],
+            src_ip_addr: [0; 4],
+            dst_ip_addr: [255; 4],
+            src_port: 68,
+            dst_port: 67,
+        }
+    }
+}
+
+/// a received frame.
+#[derive(Clone, Copy, Debug)]
+pub struct Frame<'a> {
+    pub header: FrameHeader,
+    /// the UDP payload.
+    pub payload: &'a [u8],
+}
+
+/// encode `message` in a frame.
+///
+/// returns the frame length , None when `buffer` has no room for the headers
+/// and a message.
+pub fn encode_frame(
+    message: &DHCPMessage,
+    header: &FrameHeader,
+    buffer: &mut [u8],
+) -> Option<usize> {
+    let len = message.encode(buffer.get_mut(FRAME_HEADER_LEN..)?)?;
+    write_headers(header, len, buffer)
+}
+
+/// write the headers in front of a payload of `payload_len` bytes , already at
+/// [`FRAME_HEADER_LEN`] in `buffer`.
+///
+/// returns the frame length.
+pub fn write_headers(header: &FrameHeader, payload_len: usize, buffer: &mut [u8]) -> Option<usize> {
+    let frame_len = FRAME_HEADER_LEN + payload_len;
+    let udp_len = u16::try_from(UDP_HEADER_LEN + payload_len).ok()?;
+    let ip_len = u16::try_from(IPV4_HEADER_LEN + UDP_HEADER_LEN + payload_len).ok()?;
+    let frame = buffer.get_mut(..frame_len)?;
+
+    let (ethernet, frame) = frame.split_at_mut(ETHERNET_HEADER_LEN);
+    ethernet[0..6].copy_from_slice(&header.dst_mac);
+    ethernet[6..12].copy_from_slice(&header.src_mac);
+    ethernet[12..14].copy_from_slice(&ETHERTYPE_IPV4);
+
+    let (ip, frame) = frame.split_at_mut(IPV4_HEADER_LEN);
+    ip[0] = 0x45;
+    ip[1] = 0;
+    ip[2..4].copy_from_slice(&ip_len.to_be_bytes());
+    // identification , flags and fragment offset.
+    ip[4..8].fill(0);
+    ip[8] = TTL;
+    ip[9] = PROTOCOL_UDP;
+    ip[10..12].fill(0);
+    ip[12..16].copy_from_slice(&header.src_ip_addr);
+    ip[16..20].copy_from_slice(&header.dst_ip_addr);
+    let checksum = !fold(sum(ip, 0));
+    ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+    let udp = frame;
+    udp[0..2].copy_from_slice(&header.src_port.to_be_bytes());
+    udp[2..4].copy_from_slice(&header.dst_port.to_be_bytes());
+    udp[4..6].copy_from_slice(&udp_len.to_be_bytes());
+    udp[6..8].fill(0);
+    let checksum = match !fold(sum(udp, pseudo_header_sum(header, udp_len))) {
+        // 0 means no checksum.
+        0 => 0xffff,
+        x => x,
+    };
+    udp[6..8].copy_from_slice(&checksum.to_be_bytes());
+    Some(frame_len)
+}
+
+/// unwrap a frame for `mac` , or broadcast , to UDP port `port`.
+///
+/// None when it is not one , or a checksum is wrong.
+pub fn decode_frame(frame: &[u8], mac: [u8; 6], port: u16) -> Option<Frame<'_>> {
+    let ethernet = frame.get(..ETHERNET_HEADER_LEN)?;
+    let dst_mac: [u8; 6] = ethernet[0..6].try_into().ok()?;
+    if (dst_mac != mac && dst_mac != [0xff; 6]) || ethernet[12..14] != ETHERTYPE_IPV4 {
+        return None;
+    }
+    let packet = &frame[ETHERNET_HEADER_LEN..];
+
+    let ihl = (*packet.first()? & 0x0f) as usize * 4;
+    let ip = packet.get(..ihl)?;
+    if ip[0] >> 4 != 4 || ihl < IPV4_HEADER_LEN || fold(sum(ip, 0)) != 0xffff {
+        return None;
+    }
+    // more fragments , or not the first.
+    if u16::from_be_bytes([ip[6], ip[7]]) & 0x3fff != 0 || ip[9] != PROTOCOL_UDP {
+        return None;
+    }
+    let ip_len = u16::from_be_bytes([ip[2], ip[3]]) as usize;
+    // Ethernet pads short frames.
+    let udp = packet.get(ihl..ip_len)?;
+
+    let udp_header = udp.get(..UDP_HEADER_LEN)?;
+    let udp_len = u16::from_be_bytes([udp_header[4], udp_header[5]]);
+    if (udp_len as usize) < UDP_HEADER_LEN {
+        return None;
+    }
+    let udp = udp.get(..udp_len as usize)?;
+    let header = FrameHeader {
+        src_mac: ethernet[6..12].try_into().ok()?,
+        dst_mac,
+        src_ip_addr: ip[12..16].try_into().ok()?,
+        dst_ip_addr: ip[16..20].try_into().ok()?,
+        src_port: u16::from_be_bytes([udp_header[0], udp_header[1]]),
+        dst_port: u16::from_be_bytes([udp_header[2], udp_header[3]]),
+    };
+    if header.dst_port != port {
+        return None;
+    }
+    let checksum = u16::from_be_bytes([udp_header[6], udp_header[7]]);
+    if checksum != 0 && fold(sum(udp, pseudo_header_sum(&header, udp_len))) != 0xffff {
+        return None;
+    }
+    Some(Frame {
+        header,
+        payload: &udp[UDP_HEADER_LEN..],
+    })
+}
+
+fn pseudo_header_sum(header: &FrameHeader, udp_len: u16) -> u32 {
+    let mut pseudo_header = [0; 12];
+    pseudo_header[0..4].copy_from_slice(&header.src_ip_addr);
+    pseudo_header[4..8].copy_from_slice(&header.dst_ip_addr);
+    pseudo_header[9] = PROTOCOL_UDP;
+    pseudo_header[10..12].copy_from_slice(&udp_len.to_be_bytes());
+    sum(&pseudo_header, 0)
+}
+
+/// one's complement sum of 16 bits words (RFC 1071) , odd length padded.
+fn sum(data: &[u8], initial: u32) -> u32 {
+    let mut chunks = data.chunks_exact(2);
+    let mut sum = chunks.by_ref().fold(initial, |sum, x| {
+        sum + u16::from_be_bytes([x[0], x[1]]) as u32
+    });
+    if let [x] = chunks.remainder() {
+        sum += (*x as u32) << 8;
+    }
+    sum
+}
+
+fn fold(mut sum: u32) -> u16 {
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    sum as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAC: [u8; 6] = [0x02, 0, 0, 0, 0, 0x01];
+
+    /// "hello" broadcast from [`MAC`] , checksums computed elsewhere.
+    const FRAME: [u8; 47] = [
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x08, 0x00, 0x45,
+        0x00, 0x00, 0x21, 0x00, 0x00, 0x00, 0x00, 0x40, 0x11, 0x7a, 0xcd, 0x00, 0x00, 0x00, 0x00,
+        0xff, 0xff, 0xff, 0xff, 0x00, 0x44, 0x00, 0x43, 0x00, 0x0d, 0xbb, 0x7b, 0x68, 0x65, 0x6c,
+        0x6c, 0x6f,
+    ];
+
+    #[test]
+    fn write_known_frame() {
+        let mut buffer = [0; 64];
+        buffer[FRAME_HEADER_LEN..FRAME_HEADER_LEN + 5].copy_from_slice(b"hello");
+        let len = write_headers(&FrameHeader::broadcast(MAC), 5, &mut buffer).unwrap();
+        assert_eq!(&buffer[..len], &FRAME);
+    }
+
+    #[test]
+    fn decode_known_frame() {
+        let frame = decode_frame(&FRAME, [0x02, 0, 0, 0, 0, 0x02], 67).unwrap();
+        assert_eq!(frame.header, FrameHeader::broadcast(MAC));
+        assert_eq!(frame.payload, b"hello");
+
+        // Ethernet padding is not part of the payload.
+        let mut padded = [0; 60];
+        padded[..FRAME.len()].copy_from_slice(&FRAME);
+        let frame = decode_frame(&padded, MAC, 67).unwrap();
+        assert_eq!(frame.payload, b"hello");
+
+        assert!(decode_frame(&FRAME, MAC, 68).is_none());
+    }
+
+    #[test]
+    fn wrong_checksums() {
+        let mut frame = FRAME;
+        frame[24] ^= 1;
+        assert!(decode_frame(&frame, MAC, 67).is_none());
+        let mut frame = FRAME;
+        frame[44] ^= 1;
+        assert!(decode_frame(&frame, MAC, 67).is_none());
+        // no UDP checksum.
+        let mut frame = FRAME;
+        frame[44] ^= 1;
+        frame[40..42].fill(0);
+        assert!(decode_frame(&frame, MAC, 67).is_some());
+    }
+
+    #[test]
+    fn malformed_frames() {
+        for len in 0..FRAME.len() {
+            assert!(decode_frame(&FRAME[..len], MAC, 67).is_none());
+        }
+        for udp_len in 0..UDP_HEADER_LEN as u16 {
+            let mut frame = FRAME;
+            frame[38..40].copy_from_slice(&udp_len.to_be_bytes());
+            assert!(decode_frame(&frame, MAC, 67).is_none());
+        }
+        let mut frame = FRAME;
+        frame[38..40].copy_from_slice(&0xffffu16.to_be_bytes());
+        assert!(decode_frame(&frame, MAC, 67).is_none());
+    }
+}
@@ -1,5 +1,45 @@
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod client;
+pub mod client_id;
+pub mod dhcpv6;
+pub mod dns;
+mod driver;
+#[cfg(feature = "embassy")]
+pub mod embassy;
+#[cfg(feature = "embedded-nal")]
+pub mod embedded_nal;
+pub mod frame;
+#[cfg(feature = "alloc")]
+pub mod owned;
+pub mod pool;
+pub mod pxe;
+pub mod relay;
+pub mod route;
+pub mod server;
+#[cfg(feature = "smoltcp")]
+pub mod smoltcp;
+#[cfg(feature = "std")]
+pub mod std_net;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+pub mod transport;
+pub mod vendor;
+
+use client::ClientConfig;
+use client_id::ClientId;
+use dns::{ClientFqdn, DomainSearch};
+use relay::RelayAgentInformation;
+use route::ClasslessStaticRoute;
+use vendor::{VendorClass, VendorInformation};
+
 #[derive(Clone, Copy)]
-#[repr(packed)]
+#[repr(C, packed)]
 pub struct DHCPMessageRaw {
     op: u8,
     htype: u8,
@@ -24,129 +64,9 @@ pub struct DHCPMessageRaw {
     ///Boot file name
     file: [u8; 128],
     options: [u8; 312],
+    #[allow(dead_code)]
     option_len: usize,
 }
-pub enum DecodeBuffer {
-    Offer,
-    Ack,
-    Nak,
-}
-
-impl DHCPMessageRaw {
-    pub unsafe fn decode_received_message(bytes: &[u8], received_bytes: usize) -> Self {
-        assert!(bytes.len() > (core::mem::size_of::<Self>() - core::mem::size_of::<usize>()));
-        assert!(received_bytes < core::mem::size_of::<Self>());
-        fn transmute_u16(bytes: &[u8]) -> u16 {
-            assert!(bytes.len() == 2);
-            u16::from_be_bytes([bytes[0], bytes[1]])
-        }
-        fn copy_addr(bytes: &[u8]) -> [u8; 4] {
-            assert!(bytes.len() == 4);
-            [bytes[0], bytes[1], bytes[2], bytes[3]]
-        }
-        let mut chaddr = [0; 16];
-        chaddr.copy_from_slice(&bytes[28..44]);
-        let mut sname = [0; 64];
-        sname.copy_from_slice(&bytes[44..108]);
-        let mut file = [0; 128];
-        file.copy_from_slice(&bytes[108..236]);
-        let option_len = received_bytes - 236;
-        let mut options = [0; 312];
-        options[0..(received_bytes - 236)].copy_from_slice(&bytes[236..received_bytes]);
-        Self {
-            op: bytes[0],
-            htype: bytes[1],
-            hlen: bytes[2],
-            hops: bytes[3],
-            xid: u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
-            secs: transmute_u16(&bytes[8..10]),
-            flags: transmute_u16(&bytes[10..12]),
-            ciaddr: copy_addr(&bytes[12..16]),
-            yiaddr: copy_addr(&bytes[16..20]),
-            siaddr: copy_addr(&bytes[20..24]),
-            giaddr: copy_addr(&bytes[24..28]),
-            chaddr,
-            sname,
-            file,
-            options,
-            option_len,
-        }
-    }
-
-    pub fn decode_to_rustic_message(self, decode_buffer: DecodeBuffer) -> DHCPMessage<'static> {
-        unsafe {
-            match decode_buffer {
-                DecodeBuffer::Offer => {
-                    DHCP_OFFER_DECODE_BUFFER.copy_from_slice(&self.options);
-                }
-                DecodeBuffer::Ack => {
-                    DHCP_ACK_DECODE_BUFFER.copy_from_slice(&self.options);
-                }
-                DecodeBuffer::Nak => {
-                    DHCP_NAK_DECODE_BUFFER.copy_from_slice(&self.options);
-                }
-            }
-        }
-        let decoder = OptionDecoder::from_bytes(unsafe {
-            match decode_buffer {
-                DecodeBuffer::Offer => &DHCP_OFFER_DECODE_BUFFER,
-                DecodeBuffer::Ack => &DHCP_ACK_DECODE_BUFFER,
-                DecodeBuffer::Nak => &DHCP_NAK_DECODE_BUFFER,
-            }
-        })
-        .unwrap();
-        let mut options = [
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-        ];
-        decoder
-            .zip(options.iter_mut())
-            .for_each(|(option, cell)| *cell = option);
-
-        DHCPMessage {
-            op: match self.op {
-                1 => Op::BootRequest,
-                2 => Op::BootReply,
-                _ => {
-                    unreachable!("")
-                }
-            },
-            htype: match self.htype {
-                1 => HType::Ethernet,
-                _ => {
-                    unreachable!("")
-                }
-            },
-            hlen: self.hlen,
-            hops: self.hops,
-            xid: self.xid,
-            secs: self.secs,
-            flags: match self.flags {
-                0x8000 => true,
-                0x0000 => false,
-                _ => {
-                    unreachable!("invalid flags.")
-                }
-            },
-            ciaddr: self.ciaddr,
-            yiaddr: self.yiaddr,
-            siaddr: self.siaddr,
-            giaddr: self.giaddr,
-            chaddr: self.chaddr,
-            sname: self.sname,
-            file: self.file,
-            options,
-        }
-    }
-}
 
 #[derive(Debug)]
 pub struct DHCPMessage<'a> {
@@ -164,15 +84,289 @@ pub struct DHCPMessage<'a> {
     chaddr: [u8; 16],
     sname: [u8; 64],
     file: [u8; 128],
-    pub options: [Options<'a>; 10],
+    pub options: MessageOptions<'a>,
 }
 
 /// migic cookie
 const MAGIC_COOKIE: [u8; 4] = [0x63, 0x82, 0x53, 0x63];
 
-static mut DHCP_OFFER_DECODE_BUFFER: [u8; 312] = [0; 312];
-static mut DHCP_ACK_DECODE_BUFFER: [u8; 312] = [0; 312];
-static mut DHCP_NAK_DECODE_BUFFER: [u8; 312] = [0; 312];
+/// length of the fixed part of a BOOTP message , before the magic cookie.
+pub(crate) const HEADER_LEN: usize = 236;
+
+/// [`DHCPMessage::encode`] pads shorter messages to this , the size of a
+/// message with the 312 bytes options area of RFC 2131.
+pub const MIN_MESSAGE_LEN: usize = 548;
+
+impl<'a> DHCPMessage<'a> {
+    /// decode a received message of any size.
+    ///
+    /// options are joined (RFC 3396) in `buffer` , which should be about one
+    /// and a half the length of `packet` , and read from there. options which
+    /// do not fit are dropped. None if `packet` is not a BOOTP message over
+    /// ethernet.
+    pub fn decode(packet: &[u8], buffer: &'a mut [u8]) -> Option<Self> {
+        let header = packet.get(..HEADER_LEN)?;
+        let op = match header[0] {
+            1 => Op::BootRequest,
+            2 => Op::BootReply,
+            _ => return None,
+        };
+        let htype = match header[1] {
+            1 => HType::Ethernet,
+            _ => return None,
+        };
+        let addr = |pos: usize| {
+            [
+                header[pos],
+                header[pos + 1],
+                header[pos + 2],
+                header[pos + 3],
+            ]
+        };
+        let mut chaddr = [0; 16];
+        chaddr.copy_from_slice(&header[28..44]);
+        let mut sname = [0; 64];
+        sname.copy_from_slice(&header[44..108]);
+        let mut file = [0; 128];
+        file.copy_from_slice(&header[108..236]);
+        // plain BOOTP (RFC 951) may have no magic cookie , then no options.
+        let area = &packet[HEADER_LEN..];
+        let mut len = 0;
+        if area.get(0..4) == Some(&MAGIC_COOKIE) && buffer.len() > 4 {
+            buffer[0..4].copy_from_slice(&MAGIC_COOKIE);
+            len = 4 + concatenate(&area[4..], &file, &sname, &mut buffer[4..]);
+        }
+        let buffer: &'a [u8] = buffer;
+        Some(Self {
+            op,
+            htype,
+            hlen: header[2],
+            hops: header[3],
+            xid: u32::from_be_bytes(addr(4)),
+            secs: u16::from_be_bytes([header[8], header[9]]),
+            flags: header[10] & 0x80 != 0,
+            ciaddr: addr(12),
+            yiaddr: addr(16),
+            siaddr: addr(20),
+            giaddr: addr(24),
+            chaddr,
+            sname,
+            file,
+            options: MessageOptions::Joined(JoinedOptions(&buffer[..len])),
+        })
+    }
+
+    /// write the message in `buffer` , returns the length.
+    ///
+    /// options which do not fit are dropped , the capacity is the caller's
+    /// choice , e.g. the MTU. None if `buffer` can not hold the fixed part ,
+    /// the magic cookie and End.
+    pub fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        if buffer.len() < HEADER_LEN + 5 {
+            return None;
+        }
+        self.encode_header(&mut buffer[..HEADER_LEN]);
+        let option_len = self
+            .options
+            .iter()
+            .fold(
+                OptionEncoder::new(&mut buffer[HEADER_LEN..]).init(),
+                |encoder, option| encoder.encode(&option),
+            )
+            .next_pos;
+        let len = HEADER_LEN + option_len;
+        let padded_len = len.max(MIN_MESSAGE_LEN.min(buffer.len()));
+        buffer[len..padded_len].fill(0);
+        Some(padded_len)
+    }
+
+    /// the fixed part , [`HEADER_LEN`] bytes.
+    pub(crate) fn encode_header(&self, header: &mut [u8]) {
+        let (htype, hlen) = self.htype.into();
+        header[0] = match self.op {
+            Op::BootRequest => 1,
+            Op::BootReply => 2,
+        };
+        header[1] = htype;
+        header[2] = hlen;
+        header[3] = self.hops;
+        header[4..8].copy_from_slice(&self.xid.to_be_bytes());
+        header[8..10].copy_from_slice(&self.secs.to_be_bytes());
+        header[10..12].copy_from_slice(&if self.flags { 0x8000u16 } else { 0 }.to_be_bytes());
+        header[12..16].copy_from_slice(&self.ciaddr);
+        header[16..20].copy_from_slice(&self.yiaddr);
+        header[20..24].copy_from_slice(&self.siaddr);
+        header[24..28].copy_from_slice(&self.giaddr);
+        header[28..44].copy_from_slice(&self.chaddr);
+        header[44..108].copy_from_slice(&self.sname);
+        header[108..236].copy_from_slice(&self.file);
+    }
+}
+
+/// join all instances of each option (RFC 3396) , from the options field and
+/// then the file and sname fields when overloaded.
+///
+/// each option is written once , in order of first appearance , as tag ,
+/// length (u16) and value. then End. options which do not fit are dropped.
+/// returns the length , End included.
+fn concatenate(options: &[u8], file: &[u8], sname: &[u8], buffer: &mut [u8]) -> usize {
+    let overload = EncapsulatedOptions::new(options)
+        .find(|(tag, _)| *tag == 52)
+        .and_then(|(_, x)| x.first().copied())
+        .unwrap_or(0);
+    let areas = [
+        options,
+        if overload & 1 != 0 { file } else { &[] },
+        if overload & 2 != 0 { sname } else { &[] },
+    ];
+    let instances = || areas.iter().flat_map(|x| EncapsulatedOptions::new(x));
+    // keep a byte for End.
+    let room = buffer.len() - 1;
+    let mut len = 0;
+    for (index, (tag, _)) in instances().enumerate() {
+        if instances().take(index).any(|(x, _)| x == tag) {
+            continue;
+        }
+        let start = len;
+        len += 3;
+        let complete = len <= room
+            && instances()
+                .skip(index)
+                .filter(|(x, _)| *x == tag)
+                .all(
+                    |(_, data)| match buffer[..room].get_mut(len..len + data.len()) {
+                        Some(x) => {
+                            x.copy_from_slice(data);
+                            len += data.len();
+                            true
+                        }
+                        None => false,
+                    },
+                );
+        if !complete {
+            len = start;
+            break;
+        }
+        buffer[start] = tag;
+        buffer[start + 1..start + 3].copy_from_slice(&((len - start - 3) as u16).to_be_bytes());
+    }
+    buffer[len] = 0xff;
+    len + 1
+}
+/// option 60 of [`DHCPMessage::new_discover`] and [`DHCPMessage::new_request`].
+pub const DEFAULT_VENDOR_CLASS: &str = "rust-embedded-dhcp-client";
+
+/// options a [`DHCPMessage`] to be sent holds , End included.
+pub const MAX_OPTIONS: usize = 16;
+
+/// `options` then End , the rest is Pad.
+pub(crate) fn option_list<'a>(options: &[Options<'a>]) -> MessageOptions<'a> {
+    let mut list = [Options::Pad; MAX_OPTIONS];
+    list[..options.len()].copy_from_slice(options);
+    list[options.len()] = Options::End;
+    MessageOptions::List(list)
+}
+
+/// the options of a [`DHCPMessage`].
+// no allocator to box the list with.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Copy, Debug)]
+pub enum MessageOptions<'a> {
+    /// as received , there is no limit on their number.
+    Joined(JoinedOptions<'a>),
+    /// to be sent , End then Pad.
+    List([Options<'a>; MAX_OPTIONS]),
+}
+
+/// options of a received message , all instances of each joined (RFC 3396).
+///
+/// not the wire format , only [`DHCPMessage::decode`] makes them.
+#[derive(Clone, Copy, Debug)]
+pub struct JoinedOptions<'a>(&'a [u8]);
+
+impl<'a> JoinedOptions<'a> {
+    /// tag and whole value of every option , unknown ones included.
+    pub fn values(&self) -> JoinedValues<'a> {
+        JoinedValues(self.0.get(4..).unwrap_or(&[]))
+    }
+}
+
+pub struct JoinedValues<'a>(&'a [u8]);
+
+impl<'a> Iterator for JoinedValues<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let [tag, a, b, rest @ ..] = self.0 else {
+            return None;
+        };
+        if *tag == 0xff {
+            return None;
+        }
+        let len = u16::from_be_bytes([*a, *b]) as usize;
+        let (data, rest) = rest.split_at_checked(len)?;
+        self.0 = rest;
+        Some((*tag, data))
+    }
+}
+
+impl<'a> MessageOptions<'a> {
+    pub fn iter(&self) -> MessageOptionsIter<'_, 'a> {
+        match self {
+            MessageOptions::Joined(x) => MessageOptionsIter {
+                decoder: OptionDecoder::from_bytes(x.0),
+                list: [].iter(),
+            },
+            MessageOptions::List(x) => MessageOptionsIter {
+                decoder: None,
+                list: x.iter(),
+            },
+        }
+    }
+
+    /// the list to change , a received message is turned into one first ,
+    /// without the unknown options.
+    ///
+    /// None when a received message has more options than a list holds , it
+    /// is left as is.
+    pub fn list_mut(&mut self) -> Option<&mut [Options<'a>; MAX_OPTIONS]> {
+        if let MessageOptions::Joined(_) = self {
+            let mut list = [Options::Pad; MAX_OPTIONS];
+            let mut options = self.iter().take_while(|x| !matches!(x, Options::End));
+            let mut len = 0;
+            for option in options.by_ref().take(MAX_OPTIONS - 1) {
+                list[len] = option;
+                len += 1;
+            }
+            if options.next().is_some() {
+                return None;
+            }
+            list[len] = Options::End;
+            *self = MessageOptions::List(list);
+        }
+        match self {
+            MessageOptions::List(x) => Some(x),
+            MessageOptions::Joined(_) => None,
+        }
+    }
+}
+
+pub struct MessageOptionsIter<'b, 'a> {
+    decoder: Option<OptionDecoder<'a>>,
+    list: core::slice::Iter<'b, Options<'a>>,
+}
+
+impl<'a> Iterator for MessageOptionsIter<'_, 'a> {
+    type Item = Options<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.decoder {
+            Some(x) => x.next(),
+            None => self.list.next().copied(),
+        }
+    }
+}
+
 impl<'a> DHCPMessage<'a> {
     pub fn new_discover(
         transaction_id: u32,
@@ -180,38 +374,12 @@ impl<'a> DHCPMessage<'a> {
         hw_type: HType,
         parameter_request_list: &'a [u8],
     ) -> Self {
-        let (htype, hlen) = hw_type.into();
-        let options = [
-            Options::DHCPMessageType(MessageTy::Discover),
-            Options::ClientIdentifier(htype, hardware_address.addr()),
-            Options::VendorClassIdentifier("rust-embedded-dhcp-client"),
-            Options::ParameterRequestList(parameter_request_list),
-            Options::End,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-        ];
-        let mut chaddr = [0; 16];
-        chaddr[0..hlen as usize].copy_from_slice(hardware_address.addr());
-        Self {
-            op: Op::BootRequest,
-            htype: hw_type,
-            hlen,
-            hops: 0,
-            xid: transaction_id,
-            secs: 0,
-            flags: true,
-            ciaddr: 0u32.to_be_bytes(),
-            yiaddr: 0u32.to_be_bytes(),
-            siaddr: 0u32.to_be_bytes(),
-            giaddr: 0u32.to_be_bytes(),
-            chaddr,
-            sname: [0x0; 64],
-            file: [0x0; 128],
-            options,
+        ClientConfig {
+            vendor_class: Some(DEFAULT_VENDOR_CLASS),
+            parameter_request_list,
+            ..ClientConfig::new(hardware_address, hw_type)
         }
+        .discover(transaction_id)
     }
 
     pub fn new_request(
@@ -221,19 +389,31 @@ impl<'a> DHCPMessage<'a> {
         offered_ip_addr: [u8; 4],
         server_ip_addr: [u8; 4],
     ) -> Self {
-        let (htype, hlen) = hw_type.into();
-        let options = [
-            Options::DHCPMessageType(MessageTy::Request),
-            Options::RequestedIPAddress(offered_ip_addr),
-            Options::ServerIdentifer(server_ip_addr),
-            Options::ClientIdentifier(htype, hardware_address.addr()),
-            Options::VendorClassIdentifier("rust-embedded-dhcp-client"),
-            Options::End,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-            Options::Pad,
-        ];
+        ClientConfig {
+            vendor_class: Some(DEFAULT_VENDOR_CLASS),
+            ..ClientConfig::new(hardware_address, hw_type)
+        }
+        .request(transaction_id, offered_ip_addr, server_ip_addr)
+    }
+
+    /// BOOTREQUEST without DHCP message type , for BOOTP only servers (RFC 951).
+    pub fn new_bootp_request(
+        transaction_id: u32,
+        hardware_address: HwAddress<'a>,
+        hw_type: HType,
+    ) -> Self {
+        Self::new_boot_request(transaction_id, hardware_address, hw_type, &[])
+    }
+
+    /// BOOTREQUEST from a client without an address , asking for a broadcast
+    /// reply.
+    pub(crate) fn new_boot_request(
+        transaction_id: u32,
+        hardware_address: HwAddress<'a>,
+        hw_type: HType,
+        options: &[Options<'a>],
+    ) -> Self {
+        let (_, hlen) = hw_type.into();
         let mut chaddr = [0; 16];
         chaddr[0..hlen as usize].copy_from_slice(hardware_address.addr());
         Self {
@@ -251,8 +431,213 @@ impl<'a> DHCPMessage<'a> {
             chaddr,
             sname: [0x0; 64],
             file: [0x0; 128],
-            options,
+            options: option_list(options),
+        }
+    }
+
+    /// ask the server for the two message exchange (RFC 4039).
+    ///
+    /// None when the option list is full , see [`DHCPMessage::insert_option`].
+    pub fn with_rapid_commit(mut self) -> Option<Self> {
+        self.insert_option(Options::RapidCommit)?;
+        Some(self)
+    }
+
+    /// remove every `tag` option , e.g. 82 before a reply goes to the client.
+    ///
+    /// None when a received message has more options than a list holds , see
+    /// [`MessageOptions::list_mut`].
+    pub fn remove_option(&mut self, tag: u8) -> Option<()> {
+        let options = self.options.list_mut()?;
+        let mut pos = 0;
+        for index in 0..options.len() {
+            if options[index].tag_number() != tag || matches!(options[index], Options::Pad) {
+                options[pos] = options[index];
+                pos += 1;
+            }
+        }
+        options[pos..].iter_mut().for_each(|x| *x = Options::Pad);
+        Some(())
+    }
+
+    /// send `client_id` in option 61 instead of the hardware address.
+    ///
+    /// None when the option list is full.
+    pub fn with_client_id(mut self, client_id: &'a ClientId) -> Option<Self> {
+        match self
+            .options
+            .list_mut()?
+            .iter_mut()
+            .find(|x| matches!(x, Options::ClientIdentifier(..)))
+        {
+            Some(option) => *option = client_id.option(),
+            None => self.insert_option(client_id.option())?,
+        }
+        Some(self)
+    }
+
+    /// send option 124 , e.g. our enterprise number and model.
+    ///
+    /// None when the option list is full.
+    pub fn with_vendor_class(mut self, vendor_class: VendorClass<'a>) -> Option<Self> {
+        self.insert_option(Options::VendorIdentifyingVendorClass(vendor_class))?;
+        Some(self)
+    }
+
+    /// send option 81 , e.g. to let the server update DNS for us.
+    ///
+    /// None when the option list is full.
+    pub fn with_client_fqdn(mut self, client_fqdn: ClientFqdn<'a>) -> Option<Self> {
+        self.insert_option(Options::ClientFqdn(client_fqdn))?;
+        Some(self)
+    }
+
+    /// put `option` just before End.
+    ///
+    /// None when the list holds [`MAX_OPTIONS`] already , or a received
+    /// message has more , see [`MessageOptions::list_mut`].
+    pub fn insert_option(&mut self, option: Options<'a>) -> Option<()> {
+        let options = self.options.list_mut()?;
+        let end = options.iter().position(|x| matches!(x, Options::End))?;
+        if end + 1 == options.len() {
+            return None;
+        }
+        options[end] = option;
+        options[end + 1] = Options::End;
+        Some(())
+    }
+
+    pub fn message_type(&self) -> Option<MessageTy> {
+        self.options.iter().find_map(|x| match x {
+            Options::DHCPMessageType(ty) => Some(ty),
+            _ => None,
+        })
+    }
+
+    pub fn has_rapid_commit(&self) -> bool {
+        self.options
+            .iter()
+            .any(|x| matches!(x, Options::RapidCommit))
+    }
+
+    pub fn server_identifier(&self) -> Option<[u8; 4]> {
+        self.options.iter().find_map(|x| match x {
+            Options::ServerIdentifer(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn requested_ip_addr(&self) -> Option<[u8; 4]> {
+        self.options.iter().find_map(|x| match x {
+            Options::RequestedIPAddress(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn parameter_request_list(&self) -> Option<&'a [u8]> {
+        self.options.iter().find_map(|x| match x {
+            Options::ParameterRequestList(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn vendor_specific_information(&self) -> Option<Encapsulated<'a>> {
+        self.options.iter().find_map(|x| match x {
+            Options::VendorSpecificInformation(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn vendor_identifying_vendor_class(&self) -> Option<VendorClass<'a>> {
+        self.options.iter().find_map(|x| match x {
+            Options::VendorIdentifyingVendorClass(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn vendor_identifying_vendor_information(&self) -> Option<VendorInformation<'a>> {
+        self.options.iter().find_map(|x| match x {
+            Options::VendorIdentifyingVendorInformation(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn client_fqdn(&self) -> Option<ClientFqdn<'a>> {
+        self.options.iter().find_map(|x| match x {
+            Options::ClientFqdn(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// search list for short host names.
+    pub fn domain_search(&self) -> Option<DomainSearch<'a>> {
+        self.options.iter().find_map(|x| match x {
+            Options::DomainSearch(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// option 121 , or 249 when there is no 121.
+    pub fn classless_static_route(&self) -> Option<ClasslessStaticRoute<'a>> {
+        let find = |tag| {
+            self.options.iter().find_map(|x| match x {
+                Options::ClasslessStaticRoute(x) if tag == 121 => Some(x),
+                Options::MsClasslessStaticRoute(x) if tag == 249 => Some(x),
+                _ => None,
+            })
+        };
+        find(121).or_else(|| find(249))
+    }
+
+    pub fn relay_agent_information(&self) -> Option<RelayAgentInformation<'a>> {
+        self.options.iter().find_map(|x| match x {
+            Options::RelayAgentInformation(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// reply from a BOOTP server , which has no DHCP message type.
+    pub fn is_bootp(&self) -> bool {
+        self.message_type().is_none()
+    }
+
+    /// boot server host name , from sname.
+    pub fn server_name(&self) -> Option<&str> {
+        if self.overload(OverloadMode::SName) {
+            return None;
         }
+        c_str(&self.sname)
+    }
+
+    /// boot file name , from file.
+    pub fn boot_file(&self) -> Option<&str> {
+        if self.overload(OverloadMode::File) {
+            return None;
+        }
+        c_str(&self.file)
+    }
+
+    /// sname or file holds options instead (option 52).
+    fn overload(&self, field: OverloadMode) -> bool {
+        self.options.iter().any(|x| {
+            matches!(
+                (x, field),
+                (Options::OptionOverload(OverloadMode::Both), _)
+                    | (
+                        Options::OptionOverload(OverloadMode::File),
+                        OverloadMode::File
+                    )
+                    | (
+                        Options::OptionOverload(OverloadMode::SName),
+                        OverloadMode::SName
+                    )
+            )
+        })
+    }
+
+    /// client hardware address , `hlen` bytes of chaddr.
+    pub fn chaddr(&self) -> &[u8] {
+        &self.chaddr[0..(self.hlen as usize).min(16)]
     }
 }
 
@@ -269,45 +654,44 @@ impl<'a> HwAddress<'a> {
     }
 }
 
-impl<'a> Into<HType> for HwAddress<'a> {
-    fn into(self) -> HType {
-        match self {
+impl<'a> From<HwAddress<'a>> for HType {
+    fn from(hardware_address: HwAddress<'a>) -> Self {
+        match hardware_address {
             HwAddress::Ethernet(_) => HType::Ethernet,
         }
     }
 }
 
-impl<'a> Into<DHCPMessageRaw> for DHCPMessage<'a> {
-    fn into(self) -> DHCPMessageRaw {
-        let encoder = self
+impl<'a> From<DHCPMessage<'a>> for DHCPMessageRaw {
+    fn from(message: DHCPMessage<'a>) -> Self {
+        let mut options = [0; 312];
+        let option_len = message
             .options
             .iter()
-            .fold(OptionEncoder::new().init(), |encoder, option| {
-                encoder.encode(option)
-            });
-        let OptionEncoder {
-            buffer: options,
-            next_pos: option_len,
-        } = encoder;
-        let (htype, hlen) = self.htype.into();
-        DHCPMessageRaw {
-            op: match self.op {
+            .fold(
+                OptionEncoder::new(&mut options).init(),
+                |encoder, option| encoder.encode(&option),
+            )
+            .next_pos;
+        let (htype, hlen) = message.htype.into();
+        Self {
+            op: match message.op {
                 Op::BootRequest => 1,
                 Op::BootReply => 2,
             },
             htype,
             hlen,
-            hops: self.hops,
-            xid: self.xid.to_be(),
-            secs: self.secs,
-            flags: if self.flags { 0x8000u16.to_be() } else { 0 },
-            ciaddr: self.ciaddr,
-            yiaddr: self.yiaddr,
-            siaddr: self.siaddr,
-            giaddr: self.giaddr,
-            chaddr: self.chaddr,
-            sname: self.sname,
-            file: self.file,
+            hops: message.hops,
+            xid: message.xid.to_be(),
+            secs: message.secs,
+            flags: if message.flags { 0x8000u16.to_be() } else { 0 },
+            ciaddr: message.ciaddr,
+            yiaddr: message.yiaddr,
+            siaddr: message.siaddr,
+            giaddr: message.giaddr,
+            chaddr: message.chaddr,
+            sname: message.sname,
+            file: message.file,
             options,
             option_len,
         }
@@ -344,10 +728,10 @@ pub enum HType {
     SerialLine,
 }
 
-impl Into<(u8, u8)> for HType {
+impl From<HType> for (u8, u8) {
     /// implementation for hardware type and hardware address length.
-    fn into(self) -> (u8, u8) {
-        match self {
+    fn from(hw_type: HType) -> Self {
+        match hw_type {
             HType::Ethernet => (1, 6),
             HType::ExperimentalEthernet => todo!(),
             HType::AX25 => todo!(),
@@ -374,7 +758,7 @@ impl Into<(u8, u8)> for HType {
 /// see RFC 2132 .
 ///
 /// Don't support VSI.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub enum Options<'a> {
     Pad,
     End,
@@ -402,7 +786,7 @@ pub enum Options<'a> {
     MaximumDatagramReassemblySize(u16),
     DefaultIpTTL(u8),
     PathMTUAgingTimeout(u32),
-    PathMTUPalteauTable(&'a [u16]),
+    PathMTUPalteauTable(MtuPlateaus<'a>),
     InterfaceMTU(u16),
     AllSubnetsAreLocal(bool),
     BroadCastAddress([u8; 4]),
@@ -420,7 +804,8 @@ pub enum Options<'a> {
     NetworkInformationServiceDomain(&'a str),
     NetworkInformationServers(&'a [[u8; 4]]),
     NTPServer(&'a [[u8; 4]]),
-    //VendorSpecificInformation(u16,),
+    /// see [`pxe`] for the PXE sub-options.
+    VendorSpecificInformation(Encapsulated<'a>),
     NetBIOSoverTCPIPNameServer(&'a [[u8; 4]]),
     NetBIOSoverTCPIPDatagramDistributionServer(&'a [[u8; 4]]),
     NetBIOSoverTCPIPNodeType(u8),
@@ -452,6 +837,30 @@ pub enum Options<'a> {
     RebindingTime(u32),
     VendorClassIdentifier(&'a str),
     ClientIdentifier(u8, &'a [u8]),
+    /// see RFC 4578 . first architecture type only.
+    ClientSystemArchitecture(u16),
+    /// see RFC 4578 . type , major and minor version of UNDI.
+    ClientNetworkInterfaceIdentifier(u8, u8, u8),
+    /// see RFC 4578 . type (0 for GUID) and id.
+    ClientMachineIdentifier(u8, &'a [u8]),
+    /// see RFC 4039 .
+    RapidCommit,
+    /// see RFC 3004 .
+    UserClass(UserClass<'a>),
+    /// see RFC 4702 .
+    ClientFqdn(ClientFqdn<'a>),
+    /// see RFC 3397 .
+    DomainSearch(DomainSearch<'a>),
+    /// see RFC 3442 .
+    ClasslessStaticRoute(ClasslessStaticRoute<'a>),
+    /// option 249 , same as 121.
+    MsClasslessStaticRoute(ClasslessStaticRoute<'a>),
+    /// see RFC 3925 .
+    VendorIdentifyingVendorClass(VendorClass<'a>),
+    /// see RFC 3925 .
+    VendorIdentifyingVendorInformation(VendorInformation<'a>),
+    /// see RFC 3046 .
+    RelayAgentInformation(RelayAgentInformation<'a>),
 }
 
 impl<'a> Options<'a> {
@@ -501,6 +910,7 @@ impl<'a> Options<'a> {
             Options::NetworkInformationServiceDomain(_) => 40,
             Options::NetworkInformationServers(_) => 41,
             Options::NTPServer(_) => 42,
+            Options::VendorSpecificInformation(_) => 43,
             Options::NetBIOSoverTCPIPNameServer(_) => 44,
             Options::NetBIOSoverTCPIPDatagramDistributionServer(_) => 45,
             Options::NetBIOSoverTCPIPNodeType(_) => 46,
@@ -532,47 +942,69 @@ impl<'a> Options<'a> {
             Options::RebindingTime(_) => 59,
             Options::VendorClassIdentifier(_) => 60,
             Options::ClientIdentifier(_, _) => 61,
+            Options::ClientSystemArchitecture(_) => 93,
+            Options::ClientNetworkInterfaceIdentifier(_, _, _) => 94,
+            Options::ClientMachineIdentifier(_, _) => 97,
+            Options::RapidCommit => 80,
+            Options::UserClass(_) => 77,
+            Options::ClientFqdn(_) => 81,
+            Options::DomainSearch(_) => 119,
+            Options::ClasslessStaticRoute(_) => 121,
+            Options::MsClasslessStaticRoute(_) => 249,
+            Options::VendorIdentifyingVendorClass(_) => 124,
+            Options::VendorIdentifyingVendorInformation(_) => 125,
+            Options::RelayAgentInformation(_) => 82,
         }
     }
 }
 
-struct OptionDecoder {
-    bytes: &'static [u8],
+struct OptionDecoder<'a> {
+    bytes: &'a [u8],
     next_pos: usize,
 }
-impl OptionDecoder {
-    pub fn from_bytes(bytes: &'static [u8]) -> Option<Self> {
-        if bytes[0..4] == MAGIC_COOKIE {
+impl<'a> OptionDecoder<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.get(0..4)? == MAGIC_COOKIE {
             Some(Self { bytes, next_pos: 4 })
         } else {
             None
         }
     }
 }
-impl Iterator for OptionDecoder {
-    type Item = Options<'static>;
+impl<'a> Iterator for OptionDecoder<'a> {
+    type Item = Options<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let first_byte = self.bytes[self.next_pos];
-        match first_byte {
-            0x0 => {
+        loop {
+            let first_byte = *self.bytes.get(self.next_pos)?;
+            if first_byte == 0xff {
                 self.next_pos += 1;
-                Some(Options::Pad)
+                return Some(Options::End);
             }
-            0xff => {
-                self.next_pos += 1;
-                Some(Options::End)
+            let len = u16::from_be_bytes([
+                *self.bytes.get(self.next_pos + 1)?,
+                *self.bytes.get(self.next_pos + 2)?,
+            ]) as usize;
+            let data = self.bytes.get(self.next_pos + 3..self.next_pos + 3 + len)?;
+            self.next_pos += 3 + len;
+            // unknown and malformed options are skipped.
+            if let Some(option) = Self::decode(first_byte, data) {
+                return Some(option);
             }
+        }
+    }
+}
+
+impl<'a> OptionDecoder<'a> {
+    /// `data` is the whole value , see [`concatenate`].
+    fn decode(first_byte: u8, data: &'a [u8]) -> Option<Options<'a>> {
+        match first_byte {
             //[u8;4]
             1 | 2 | 16 | 28 | 32 | 50 | 54 => {
-                let data = [
-                    self.bytes[self.next_pos + 2],
-                    self.bytes[self.next_pos + 3],
-                    self.bytes[self.next_pos + 4],
-                    self.bytes[self.next_pos + 5],
-                ];
-
-                self.next_pos += 6;
+                let &[a, b, c, d] = data else {
+                    return None;
+                };
+                let data = [a, b, c, d];
                 Some(match first_byte {
                     1 => Options::SubNetMask(data),
                     2 => Options::TimeOffset(data),
@@ -589,12 +1021,7 @@ impl Iterator for OptionDecoder {
             //&[[u8;4]]
             3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 41 | 42 | 44 | 45 | 48 | 49 | 65 | 68 | 69
             | 70 | 71 | 72 | 73 | 74 | 75 | 76 => {
-                let ptr = (&self.bytes[self.next_pos + 2] as *const u8) as *const [u8; 4];
-                let bytes_of_data = self.bytes[self.next_pos + 1];
-                let slice =
-                    unsafe { core::slice::from_raw_parts(ptr, (bytes_of_data / 4) as usize) };
-
-                self.next_pos += 2 + bytes_of_data as usize;
+                let (slice, _) = data.as_chunks::<4>();
                 Some(match first_byte {
                     3 => Options::Router(slice),
                     4 => Options::TimeServer(slice),
@@ -628,10 +1055,7 @@ impl Iterator for OptionDecoder {
             }
             //&str
             12 | 14 | 15 | 17 | 18 | 40 | 47 | 64 | 66 | 67 | 56 | 60 => {
-                let ptr = &self.bytes
-                    [self.next_pos + 2..self.next_pos + 2 + self.bytes[self.next_pos + 1] as usize];
-
-                let txt = core::str::from_utf8(ptr).unwrap();
+                let txt = core::str::from_utf8(data).ok()?;
                 Some(match first_byte {
                     12 => Options::Hostname(txt),
                     14 => Options::MeritDumpFile(txt),
@@ -652,10 +1076,10 @@ impl Iterator for OptionDecoder {
             }
             //u16
             13 | 22 | 26 | 57 => {
-                let x = [self.bytes[self.next_pos + 2], self.bytes[self.next_pos + 3]];
-                let x: u16 = unsafe { core::mem::transmute(x) };
-                let x = u16::from_be(x);
-                self.next_pos += 4;
+                let &[a, b] = data else {
+                    return None;
+                };
+                let x = u16::from_be_bytes([a, b]);
                 Some(match first_byte {
                     13 => Options::BootfileSize(x),
                     22 => Options::MaximumDatagramReassemblySize(x),
@@ -666,9 +1090,10 @@ impl Iterator for OptionDecoder {
             }
             //bool
             19 | 20 | 27 | 29 | 30 | 31 | 34 | 36 | 39 => {
-                let x = self.bytes[self.next_pos + 2];
-                let x = if x == 0 { false } else { true };
-                self.next_pos += 3;
+                let &[x] = data else {
+                    return None;
+                };
+                let x = x != 0;
                 Some(match first_byte {
                     19 => Options::IpForwarding(x),
                     20 => Options::NonLocalSourceRouting(x),
@@ -686,12 +1111,8 @@ impl Iterator for OptionDecoder {
             }
             //&[([u8;4],[u8;4])]
             21 | 33 => {
-                let ptr =
-                    (&self.bytes[self.next_pos + 2] as *const u8) as *const ([u8; 4], [u8; 4]);
-                let bytes_of_data = self.bytes[self.next_pos + 1];
-                let slice =
-                    unsafe { core::slice::from_raw_parts(ptr, (bytes_of_data / 8) as usize) };
-                self.next_pos += 2 + bytes_of_data as usize;
+                let ptr = data.as_ptr() as *const ([u8; 4], [u8; 4]);
+                let slice = unsafe { core::slice::from_raw_parts(ptr, data.len() / 8) };
                 Some(match first_byte {
                     21 => Options::PolicyFilter(slice),
                     33 => Options::StaticRoute(slice),
@@ -701,10 +1122,10 @@ impl Iterator for OptionDecoder {
                 })
             }
             // u8
-            23 | 37 | 46 | 53 => {
-                let x = self.bytes[self.next_pos + 2];
-                self.next_pos += 3;
-
+            23 | 37 | 46 | 52 | 53 => {
+                let &[x] = data else {
+                    return None;
+                };
                 Some(match first_byte {
                     23 => Options::DefaultIpTTL(x),
                     37 => Options::TCPDefaultTTL(x),
@@ -713,7 +1134,7 @@ impl Iterator for OptionDecoder {
                         1 => OverloadMode::File,
                         2 => OverloadMode::SName,
                         3 => OverloadMode::Both,
-                        _ => unreachable!(),
+                        _ => return None,
                     }),
                     53 => Options::DHCPMessageType(match x {
                         1 => MessageTy::Discover,
@@ -724,22 +1145,17 @@ impl Iterator for OptionDecoder {
                         6 => MessageTy::Nak,
                         7 => MessageTy::Release,
                         8 => MessageTy::Inform,
-                        _ => unreachable!(),
+                        _ => return None,
                     }),
                     _ => unreachable!(),
                 })
             }
             //u32
             24 | 35 | 38 | 51 | 58 | 59 => {
-                let x = [
-                    self.bytes[self.next_pos + 2],
-                    self.bytes[self.next_pos + 3],
-                    self.bytes[self.next_pos + 4],
-                    self.bytes[self.next_pos + 5],
-                ];
-                let x: u32 = unsafe { core::mem::transmute(x) };
-                let x = u32::from_be(x);
-                self.next_pos += 6;
+                let &[a, b, c, d] = data else {
+                    return None;
+                };
+                let x = u32::from_be_bytes([a, b, c, d]);
                 Some(match first_byte {
                     24 => Options::PathMTUAgingTimeout(x),
                     35 => Options::ARPCacheTimeout(x),
@@ -752,50 +1168,73 @@ impl Iterator for OptionDecoder {
                 })
             }
             //&[u16]
-            25 => {
-                let index = self.next_pos + 2;
-                let ptr = (&self.bytes[index] as *const u8) as *mut u16;
-                let bytes_of_data = self.bytes[self.next_pos + 1];
-                let slice =
-                    unsafe { core::slice::from_raw_parts_mut(ptr, (bytes_of_data / 2) as usize) };
-                slice.iter_mut().for_each(|ptr| {
-                    *ptr = u16::from_be(*ptr);
-                });
-                self.next_pos += 2 + bytes_of_data as usize;
-                Some(match first_byte {
-                    25 => Options::PathMTUPalteauTable(slice),
-                    _ => {
-                        unreachable!()
-                    }
-                })
-            }
-            55 => {
-                let ptr = &self.bytes
-                    [self.next_pos + 2..self.next_pos + 2 + self.bytes[self.next_pos + 1] as usize];
-                self.next_pos += 2 + self.bytes[self.next_pos + 1] as usize;
-                Some(Options::ParameterRequestList(ptr))
+            25 if data.len().is_multiple_of(2) => {
+                Some(Options::PathMTUPalteauTable(MtuPlateaus::Bytes(data)))
             }
+            55 => Some(Options::ParameterRequestList(data)),
             61 => {
-                let len = (self.bytes[self.next_pos + 1] - 1) as usize;
-                let ty = self.bytes[self.next_pos + 2];
-                let slice = &self.bytes[self.next_pos + 3..self.next_pos + 3 + len];
-                self.next_pos += 3 + len;
+                let (&ty, slice) = data.split_first()?;
                 Some(Options::ClientIdentifier(ty, slice))
             }
+            43 => Some(Options::VendorSpecificInformation(Encapsulated::Bytes(
+                data,
+            ))),
+            93 => {
+                let &[a, b] = data else {
+                    return None;
+                };
+                Some(Options::ClientSystemArchitecture(u16::from_be_bytes([
+                    a, b,
+                ])))
+            }
+            94 => {
+                let &[ty, major, minor] = data else {
+                    return None;
+                };
+                Some(Options::ClientNetworkInterfaceIdentifier(ty, major, minor))
+            }
+            97 => {
+                let (&ty, slice) = data.split_first()?;
+                Some(Options::ClientMachineIdentifier(ty, slice))
+            }
+            80 => Some(Options::RapidCommit),
+            77 => Some(Options::UserClass(UserClass::Bytes(data))),
+            81 => ClientFqdn::decode(data).map(Options::ClientFqdn),
+            119 => Some(Options::DomainSearch(DomainSearch::Bytes(data))),
+            121 | 249 => {
+                let routes = ClasslessStaticRoute::Bytes(data);
+                Some(match first_byte {
+                    121 => Options::ClasslessStaticRoute(routes),
+                    249 => Options::MsClasslessStaticRoute(routes),
+                    _ => unreachable!(),
+                })
+            }
+            124 => Some(Options::VendorIdentifyingVendorClass(VendorClass::Bytes(
+                data,
+            ))),
+            125 => Some(Options::VendorIdentifyingVendorInformation(
+                VendorInformation::Bytes(data),
+            )),
+            82 => Some(Options::RelayAgentInformation(
+                RelayAgentInformation::Bytes(data),
+            )),
             _ => None,
         }
     }
 }
 
-struct OptionEncoder {
-    buffer: [u8; 312],
+/// writes in a buffer of any size , options which do not fit are dropped.
+/// there is always room for End.
+struct OptionEncoder<'b> {
+    buffer: &'b mut [u8],
     next_pos: usize,
 }
 
-impl OptionEncoder {
-    fn new() -> Self {
+impl<'b> OptionEncoder<'b> {
+    /// `buffer` must hold at least the magic cookie and End.
+    fn new(buffer: &'b mut [u8]) -> Self {
         Self {
-            buffer: [0; 312],
+            buffer,
             next_pos: 0,
         }
     }
@@ -804,7 +1243,137 @@ impl OptionEncoder {
         self.next_pos = 4;
         self
     }
+    /// write length and value of the option whose tag was just written.
+    ///
+    /// a value longer than 255 bytes is split in several instances (RFC 3396).
+    /// the option , tag included , is dropped when the value does not fit or
+    /// can not be encoded.
+    fn encode_value(&mut self, encode: impl FnOnce(&mut [u8]) -> Option<usize>) {
+        let tag = self.buffer[self.next_pos - 1];
+        let start = self.next_pos + 1;
+        let room = self.buffer.len() - 1;
+        let Some((len, instances)) = encode(&mut self.buffer[start..room])
+            .map(|len| (len, len.div_ceil(255).max(1)))
+            .filter(|(len, instances)| start + len + 2 * (instances - 1) <= room)
+        else {
+            self.next_pos -= 1;
+            return;
+        };
+        // from the last part , so nothing is overwritten before it moved.
+        for index in (1..instances).rev() {
+            let from = start + 255 * index;
+            let part_len = (len - 255 * index).min(255);
+            let to = from + 2 * index;
+            self.buffer.copy_within(from..from + part_len, to);
+            self.buffer[to - 2] = tag;
+            self.buffer[to - 1] = part_len as u8;
+        }
+        self.buffer[self.next_pos] = len.min(255) as u8;
+        self.next_pos = start + len + 2 * (instances - 1);
+    }
+
+    /// bytes written for `option` , the least for those written by
+    /// [`OptionEncoder::encode_value`] which checks the room itself.
+    fn encoded_len(option: &Options) -> usize {
+        match option {
+            Options::Pad | Options::End => 1,
+            Options::Router(_)
+            | Options::TimeServer(_)
+            | Options::NameServer(_)
+            | Options::DNS(_)
+            | Options::LogServer(_)
+            | Options::CookieServer(_)
+            | Options::LPRServer(_)
+            | Options::ImpressServer(_)
+            | Options::ResourceLocationServer(_)
+            | Options::NetworkInformationServers(_)
+            | Options::NTPServer(_)
+            | Options::NetBIOSoverTCPIPNameServer(_)
+            | Options::NetBIOSoverTCPIPDatagramDistributionServer(_)
+            | Options::XWindowSystemFontServer(_)
+            | Options::XWindowSystemDisplayManager(_)
+            | Options::NetworkInformationServicePlusServers(_)
+            | Options::MobileIPHomeAgent(_)
+            | Options::SMTPServer(_)
+            | Options::POPServer(_)
+            | Options::NNTPServer(_)
+            | Options::DefaultWWWServer(_)
+            | Options::DefaultFingerServer(_)
+            | Options::DefaultIRCServer(_)
+            | Options::StreetTalkServer(_)
+            | Options::StreetTalkDirectoryAssistanceServer(_)
+            | Options::Hostname(_)
+            | Options::MeritDumpFile(_)
+            | Options::DomainName(_)
+            | Options::RootPath(_)
+            | Options::ExtensionPath(_)
+            | Options::NetworkInformationServiceDomain(_)
+            | Options::NetBIOSoverTCPIPScope(_)
+            | Options::NetworkInformationServicePlusDomain(_)
+            | Options::TFTPServerName(_)
+            | Options::BootFileName(_)
+            | Options::Message(_)
+            | Options::VendorClassIdentifier(_)
+            | Options::ParameterRequestList(_)
+            | Options::ClientIdentifier(..)
+            | Options::ClientMachineIdentifier(..)
+            | Options::PolicyFilter(_)
+            | Options::StaticRoute(_)
+            | Options::PathMTUPalteauTable(_)
+            | Options::VendorSpecificInformation(_)
+            | Options::UserClass(_)
+            | Options::ClientFqdn(_)
+            | Options::DomainSearch(_)
+            | Options::ClasslessStaticRoute(_)
+            | Options::MsClasslessStaticRoute(_)
+            | Options::VendorIdentifyingVendorClass(_)
+            | Options::VendorIdentifyingVendorInformation(_)
+            | Options::RelayAgentInformation(_) => 2,
+            Options::SubNetMask(_)
+            | Options::TimeOffset(_)
+            | Options::SwapServer(_)
+            | Options::BroadCastAddress(_)
+            | Options::RouterSolicitationAddress(_)
+            | Options::RequestedIPAddress(_)
+            | Options::ServerIdentifer(_)
+            | Options::PathMTUAgingTimeout(_)
+            | Options::TCPKeepaliveInterval(_)
+            | Options::ARPCacheTimeout(_)
+            | Options::IPAddressLeaseTime(_)
+            | Options::RenewalTime(_)
+            | Options::RebindingTime(_) => 6,
+            Options::ClientNetworkInterfaceIdentifier(..) => 5,
+            Options::BootfileSize(_)
+            | Options::MaximumDatagramReassemblySize(_)
+            | Options::InterfaceMTU(_)
+            | Options::MaximumDHCPMessageSize(_)
+            | Options::ClientSystemArchitecture(_) => 4,
+            Options::DefaultIpTTL(_)
+            | Options::NetBIOSoverTCPIPNodeType(_)
+            | Options::TCPDefaultTTL(_)
+            | Options::IpForwarding(_)
+            | Options::NonLocalSourceRouting(_)
+            | Options::AllSubnetsAreLocal(_)
+            | Options::PerformMaskDiscovery(_)
+            | Options::MaskSupplier(_)
+            | Options::PerformRouterDiscovery(_)
+            | Options::TrailerEncapsulation(_)
+            | Options::EthernetEncapsulation(_)
+            | Options::TCPKeepaliveGarbage(_)
+            | Options::OptionOverload(_)
+            | Options::DHCPMessageType(_) => 3,
+            Options::RapidCommit => 2,
+        }
+    }
+
     fn encode(mut self, option: &Options) -> Self {
+        let room = match option {
+            Options::End => self.buffer.len(),
+            _ => self.buffer.len() - 1,
+        };
+        if self.next_pos + Self::encoded_len(option) > room {
+            return self;
+        }
         let first_byte = option.tag_number();
         self.buffer[self.next_pos] = first_byte;
         self.next_pos += 1;
@@ -849,13 +1418,12 @@ impl OptionEncoder {
             | Options::DefaultIRCServer(x)
             | Options::StreetTalkServer(x)
             | Options::StreetTalkDirectoryAssistanceServer(x) => {
-                self.buffer[self.next_pos] = 4 * x.len() as u8;
-                self.next_pos += 1;
-                for addr in *x {
-                    self.buffer[self.next_pos..self.next_pos + 4].copy_from_slice(addr);
-                    self.next_pos += 4;
-                }
-                self.next_pos += 1;
+                self.encode_value(|buffer| {
+                    x.iter().try_fold(0, |len, addr| {
+                        buffer.get_mut(len..len + 4)?.copy_from_slice(addr);
+                        Some(len + 4)
+                    })
+                });
             }
 
             Options::Hostname(x)
@@ -870,11 +1438,7 @@ impl OptionEncoder {
             | Options::BootFileName(x)
             | Options::Message(x)
             | Options::VendorClassIdentifier(x) => {
-                self.buffer[self.next_pos] = x.len() as u8;
-                self.next_pos += 1;
-                self.buffer[self.next_pos..self.next_pos + x.len()].copy_from_slice(x.as_bytes());
-                self.next_pos += x.len();
-                self.next_pos += 1;
+                self.encode_value(|buffer| encode_bytes(x.as_bytes(), buffer));
             }
 
             Options::DefaultIpTTL(x)
@@ -893,7 +1457,7 @@ impl OptionEncoder {
                 self.buffer[self.next_pos] = 2;
                 self.next_pos += 1;
                 self.buffer[self.next_pos..self.next_pos + 2].copy_from_slice(&x.to_be_bytes());
-                self.next_pos += 1;
+                self.next_pos += 2;
             }
 
             Options::PathMTUAgingTimeout(x)
@@ -905,10 +1469,12 @@ impl OptionEncoder {
                 self.buffer[self.next_pos] = 4;
                 self.next_pos += 1;
                 self.buffer[self.next_pos..self.next_pos + 4].copy_from_slice(&x.to_be_bytes());
-                self.next_pos += 1;
+                self.next_pos += 4;
             }
 
-            Options::PathMTUPalteauTable(x) => todo!(),
+            Options::PathMTUPalteauTable(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
+            }
 
             Options::IpForwarding(x)
             | Options::NonLocalSourceRouting(x)
@@ -926,7 +1492,16 @@ impl OptionEncoder {
                 self.next_pos += 1;
             }
 
-            Options::PolicyFilter(x) | Options::StaticRoute(x) => todo!(),
+            Options::PolicyFilter(x) | Options::StaticRoute(x) => {
+                self.encode_value(|buffer| {
+                    x.iter().try_fold(0, |len, (addr, mask)| {
+                        let buffer = buffer.get_mut(len..len + 8)?;
+                        buffer[0..4].copy_from_slice(addr);
+                        buffer[4..8].copy_from_slice(mask);
+                        Some(len + 8)
+                    })
+                });
+            }
 
             Options::OptionOverload(x) => {
                 self.buffer[self.next_pos] = 1;
@@ -957,31 +1532,298 @@ impl OptionEncoder {
                 self.next_pos += 1;
             }
             Options::ParameterRequestList(x) => {
-                self.buffer[self.next_pos] = x.len() as u8;
-                self.next_pos += 1;
-                self.buffer[self.next_pos..self.next_pos + x.len()].copy_from_slice(x);
-                self.next_pos += x.len();
+                self.encode_value(|buffer| encode_bytes(x, buffer));
             }
             Options::ClientIdentifier(x, y) => {
-                self.buffer[self.next_pos] = (y.len() + 1) as u8;
+                self.encode_value(|buffer| {
+                    *buffer.first_mut()? = *x;
+                    Some(1 + encode_bytes(y, &mut buffer[1..])?)
+                });
+            }
+            Options::VendorSpecificInformation(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
+            }
+            Options::ClientSystemArchitecture(x) => {
+                self.buffer[self.next_pos] = 2;
                 self.next_pos += 1;
-                self.buffer[self.next_pos] = *x;
+                self.buffer[self.next_pos..self.next_pos + 2].copy_from_slice(&x.to_be_bytes());
+                self.next_pos += 2;
+            }
+            Options::ClientNetworkInterfaceIdentifier(ty, major, minor) => {
+                self.buffer[self.next_pos] = 3;
                 self.next_pos += 1;
-                self.buffer[self.next_pos..self.next_pos + y.len()].copy_from_slice(y);
-                self.next_pos += y.len();
+                self.buffer[self.next_pos..self.next_pos + 3]
+                    .copy_from_slice(&[*ty, *major, *minor]);
+                self.next_pos += 3;
+            }
+            Options::ClientMachineIdentifier(x, y) => {
+                self.encode_value(|buffer| {
+                    *buffer.first_mut()? = *x;
+                    Some(1 + encode_bytes(y, &mut buffer[1..])?)
+                });
+            }
+            Options::RapidCommit => {
+                self.buffer[self.next_pos] = 0;
+                self.next_pos += 1;
+            }
+            Options::ClasslessStaticRoute(x) | Options::MsClasslessStaticRoute(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
+            }
+            Options::UserClass(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
+            }
+            Options::DomainSearch(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
+            }
+            Options::ClientFqdn(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
+            }
+            Options::VendorIdentifyingVendorClass(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
+            }
+            Options::VendorIdentifyingVendorInformation(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
+            }
+            Options::RelayAgentInformation(x) => {
+                self.encode_value(|buffer| x.encode(buffer));
             }
         }
         self
     }
 }
 
-#[derive(Debug)]
+/// copy `bytes` at the start of `buffer` , returns the length.
+fn encode_bytes(bytes: &[u8], buffer: &mut [u8]) -> Option<usize> {
+    buffer.get_mut(..bytes.len())?.copy_from_slice(bytes);
+    Some(bytes.len())
+}
+
+/// NUL terminated string , None when empty.
+fn c_str(bytes: &[u8]) -> Option<&str> {
+    let len = bytes.iter().position(|x| *x == 0).unwrap_or(bytes.len());
+    core::str::from_utf8(&bytes[0..len])
+        .ok()
+        .filter(|x| !x.is_empty())
+}
+
+/// value of an option carrying (code , data) pairs , like 43 (RFC 2132 8.4).
+#[derive(Clone, Copy, Debug)]
+pub enum Encapsulated<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    SubOptions(&'a [(u8, &'a [u8])]),
+}
+
+impl<'a> Encapsulated<'a> {
+    /// raw value , None for [`Encapsulated::SubOptions`].
+    pub fn bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            Encapsulated::Bytes(x) => Some(x),
+            Encapsulated::SubOptions(_) => None,
+        }
+    }
+
+    pub fn iter(&self) -> EncapsulatedIter<'a> {
+        match self {
+            Encapsulated::Bytes(x) => EncapsulatedIter::Bytes(EncapsulatedOptions::new(x)),
+            Encapsulated::SubOptions(x) => EncapsulatedIter::SubOptions(x.iter()),
+        }
+    }
+
+    /// data of the first sub-option `code`.
+    pub fn get(&self, code: u8) -> Option<&'a [u8]> {
+        self.iter().find(|(x, _)| *x == code).map(|(_, x)| x)
+    }
+
+    /// write the value (without code and length) , returns the length.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            Encapsulated::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                Some(x.len())
+            }
+            Encapsulated::SubOptions(x) => x.iter().try_fold(0, |len, (code, data)| {
+                let buffer = buffer.get_mut(len..len + 2 + data.len())?;
+                buffer[0] = *code;
+                buffer[1] = u8::try_from(data.len()).ok()?;
+                buffer[2..].copy_from_slice(data);
+                Some(len + 2 + data.len())
+            }),
+        }
+    }
+}
+
+pub enum EncapsulatedIter<'a> {
+    Bytes(EncapsulatedOptions<'a>),
+    SubOptions(core::slice::Iter<'a, (u8, &'a [u8])>),
+}
+
+impl<'a> Iterator for EncapsulatedIter<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            EncapsulatedIter::Bytes(x) => x.next(),
+            EncapsulatedIter::SubOptions(x) => x.next().copied(),
+        }
+    }
+}
+
+/// (code , data) encapsulated in an option , see RFC 2132 8.4 .
+#[derive(Clone, Copy, Debug)]
+pub struct EncapsulatedOptions<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> EncapsulatedOptions<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+}
+
+impl<'a> Iterator for EncapsulatedOptions<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (&code, rest) = self.bytes.split_first()?;
+            match code {
+                0 => self.bytes = rest,
+                255 => {
+                    self.bytes = &[];
+                    return None;
+                }
+                _ => {
+                    let (&len, rest) = rest.split_first()?;
+                    if rest.len() < len as usize {
+                        self.bytes = &[];
+                        return None;
+                    }
+                    let (data, rest) = rest.split_at(len as usize);
+                    self.bytes = rest;
+                    return Some((code, data));
+                }
+            }
+        }
+    }
+}
+
+/// value of option 77 (RFC 3004).
+#[derive(Clone, Copy, Debug)]
+pub enum UserClass<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    Classes(&'a [&'a str]),
+}
+
+impl<'a> UserClass<'a> {
+    pub fn iter(&self) -> UserClasses<'a> {
+        match self {
+            UserClass::Bytes(x) => UserClasses::Bytes(x),
+            UserClass::Classes(x) => UserClasses::Classes(x.iter()),
+        }
+    }
+
+    /// write the value (without code and length) , returns the length.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            UserClass::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                Some(x.len())
+            }
+            UserClass::Classes(x) => x.iter().try_fold(0, |len, class| {
+                let buffer = buffer.get_mut(len..len + 1 + class.len())?;
+                buffer[0] = u8::try_from(class.len()).ok()?;
+                buffer[1..].copy_from_slice(class.as_bytes());
+                Some(len + 1 + class.len())
+            }),
+        }
+    }
+}
+
+pub enum UserClasses<'a> {
+    Bytes(&'a [u8]),
+    Classes(core::slice::Iter<'a, &'a str>),
+}
+
+impl<'a> Iterator for UserClasses<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            UserClasses::Bytes(bytes) => {
+                let (&len, rest) = bytes.split_first()?;
+                let Some((data, rest)) = rest.split_at_checked(len as usize) else {
+                    *bytes = &[];
+                    return None;
+                };
+                *bytes = rest;
+                Some(data)
+            }
+            UserClasses::Classes(x) => x.next().map(|x| x.as_bytes()),
+        }
+    }
+}
+
+/// value of option 25 , MTU sizes from the smallest.
+#[derive(Clone, Copy, Debug)]
+pub enum MtuPlateaus<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    Sizes(&'a [u16]),
+}
+
+impl<'a> MtuPlateaus<'a> {
+    pub fn iter(&self) -> MtuPlateausIter<'a> {
+        match self {
+            MtuPlateaus::Bytes(x) => MtuPlateausIter::Bytes(x.chunks_exact(2)),
+            MtuPlateaus::Sizes(x) => MtuPlateausIter::Sizes(x.iter()),
+        }
+    }
+
+    /// write the value (without code and length) , returns the length.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            MtuPlateaus::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                Some(x.len())
+            }
+            MtuPlateaus::Sizes(x) => x.iter().try_fold(0, |len, size| {
+                buffer
+                    .get_mut(len..len + 2)?
+                    .copy_from_slice(&size.to_be_bytes());
+                Some(len + 2)
+            }),
+        }
+    }
+}
+
+pub enum MtuPlateausIter<'a> {
+    Bytes(core::slice::ChunksExact<'a, u8>),
+    Sizes(core::slice::Iter<'a, u16>),
+}
+
+impl Iterator for MtuPlateausIter<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            MtuPlateausIter::Bytes(x) => x.next().map(|x| u16::from_be_bytes([x[0], x[1]])),
+            MtuPlateausIter::Sizes(x) => x.next().copied(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub enum OverloadMode {
     File,
     SName,
     Both,
 }
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MessageTy {
     Discover,
     Offer,
@@ -992,3 +1834,257 @@ pub enum MessageTy {
     Release,
     Inform,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a BOOTREPLY with `options` after the magic cookie , returns its length.
+    pub(crate) fn reply(options: &[u8], buffer: &mut [u8]) -> usize {
+        buffer[..HEADER_LEN].fill(0);
+        buffer[0] = 2;
+        buffer[1] = 1;
+        buffer[2] = 6;
+        buffer[HEADER_LEN..HEADER_LEN + 4].copy_from_slice(&MAGIC_COOKIE);
+        buffer[HEADER_LEN + 4..HEADER_LEN + 4 + options.len()].copy_from_slice(options);
+        HEADER_LEN + 4 + options.len()
+    }
+
+    #[test]
+    fn vendor_specific_information() {
+        let sub_options: [(u8, &[u8]); 2] = [(1, &[10, 0, 0, 1]), (2, b"abc")];
+        let mut message = DHCPMessage::new_bootp_request(
+            1,
+            HwAddress::Ethernet(&[2, 0, 0, 0, 0, 1]),
+            HType::Ethernet,
+        );
+        message
+            .insert_option(Options::VendorSpecificInformation(
+                Encapsulated::SubOptions(&sub_options),
+            ))
+            .unwrap();
+        let mut packet = [0; 600];
+        let len = message.encode(&mut packet).unwrap();
+        assert_eq!(
+            packet[HEADER_LEN + 4..HEADER_LEN + 4 + 14],
+            [43, 11, 1, 4, 10, 0, 0, 1, 2, 3, b'a', b'b', b'c', 0xff]
+        );
+        let mut buffer = [0; 900];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let information = message.vendor_specific_information().unwrap();
+        assert!(information.iter().eq(sub_options));
+        assert_eq!(information.get(2), Some(&b"abc"[..]));
+        assert_eq!(information.get(3), None);
+    }
+
+    #[test]
+    fn truncated_sub_option() {
+        let mut packet = [0; 600];
+        // Pad , a sub-option , then one longer than what is left.
+        let len = reply(&[43, 8, 0, 1, 1, 7, 2, 5, 1, 2, 0xff], &mut packet);
+        let mut buffer = [0; 900];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let information = message.vendor_specific_information().unwrap();
+        assert_eq!(information.bytes(), Some(&[0, 1, 1, 7, 2, 5, 1, 2][..]));
+        assert!(information.iter().eq([(1, &[7][..])]));
+        assert_eq!(information.get(2), None);
+    }
+
+    #[test]
+    fn path_mtu_plateau_table() {
+        let mut packet = [0; 600];
+        // odd offset , so the sizes are not aligned.
+        let len = reply(&[0, 25, 4, 0x05, 0xdc, 0x02, 0x40, 0xff], &mut packet);
+        let mut buffer = [0; 600];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let table = message
+            .options
+            .iter()
+            .find_map(|x| match x {
+                Options::PathMTUPalteauTable(x) => Some(x),
+                _ => None,
+            })
+            .unwrap();
+        assert!(table.iter().eq([1500, 576]));
+        assert!(table.iter().eq([1500, 576]));
+        assert_eq!(&buffer[4..11], &[25, 0, 4, 0x05, 0xdc, 0x02, 0x40]);
+
+        let sizes = [1500, 576];
+        let mut encoded = [0; 16];
+        let len = OptionEncoder::new(&mut encoded)
+            .init()
+            .encode(&Options::PathMTUPalteauTable(MtuPlateaus::Sizes(&sizes)))
+            .next_pos;
+        assert_eq!(&encoded[4..len], &[25, 4, 0x05, 0xdc, 0x02, 0x40]);
+    }
+
+    #[test]
+    fn concatenate_options_file_and_sname() {
+        let mut packet = [0; 600];
+        let len = reply(&[52, 1, 3, 12, 3, b'a', b'b', b'c', 0xff], &mut packet);
+        packet[108..114].copy_from_slice(&[12, 3, b'd', b'e', b'f', 0xff]);
+        packet[44..49].copy_from_slice(&[12, 2, b'g', b'h', 0xff]);
+        let mut buffer = [0; 600];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let hostname = message.options.iter().find_map(|x| match x {
+            Options::Hostname(x) => Some(x),
+            _ => None,
+        });
+        assert_eq!(hostname, Some("abcdefgh"));
+        assert_eq!(message.boot_file(), None);
+        assert_eq!(message.server_name(), None);
+    }
+
+    #[test]
+    fn file_and_sname_without_overload() {
+        let mut packet = [0; 600];
+        let len = reply(&[12, 3, b'a', b'b', b'c', 0xff], &mut packet);
+        packet[108..114].copy_from_slice(&[12, 3, b'd', b'e', b'f', 0xff]);
+        let mut buffer = [0; 600];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let hostname = message.options.iter().find_map(|x| match x {
+            Options::Hostname(x) => Some(x),
+            _ => None,
+        });
+        assert_eq!(hostname, Some("abc"));
+    }
+
+    #[test]
+    fn split_long_value() {
+        let hostname = core::str::from_utf8(&[b'h'; 300]).unwrap();
+        let message = ClientConfig {
+            hostname: Some(hostname),
+            ..ClientConfig::new(HwAddress::Ethernet(&[2, 0, 0, 0, 0, 1]), HType::Ethernet)
+        }
+        .discover(1);
+        let mut packet = [0; 1500];
+        let len = message.encode(&mut packet).unwrap();
+        let options = &packet[HEADER_LEN + 4..len];
+        let start = options.windows(2).position(|x| x == [12, 255]).unwrap();
+        assert_eq!(&options[start + 257..start + 259], &[12, 45]);
+
+        let mut buffer = [0; 1500];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let decoded = message.options.iter().find_map(|x| match x {
+            Options::Hostname(x) => Some(x),
+            _ => None,
+        });
+        assert_eq!(decoded, Some(hostname));
+    }
+
+    #[test]
+    fn drop_option_which_does_not_fit() {
+        let mut buffer = [0; 20];
+        let encoder = OptionEncoder::new(&mut buffer)
+            .init()
+            .encode(&Options::Hostname("a-hostname-longer-than-the-room"))
+            .encode(&Options::DHCPMessageType(MessageTy::Discover))
+            .encode(&Options::End);
+        let len = encoder.next_pos;
+        assert_eq!(&buffer[4..len], &[53, 1, 1, 0xff]);
+
+        // a label longer than 63 bytes can not be encoded.
+        let name = core::str::from_utf8(&[b'x'; 64]).unwrap();
+        let mut buffer = [0; 128];
+        let encoder = OptionEncoder::new(&mut buffer)
+            .init()
+            .encode(&Options::ClientFqdn(ClientFqdn::new(name)))
+            .encode(&Options::End);
+        let len = encoder.next_pos;
+        assert_eq!(&buffer[4..len], &[0xff]);
+    }
+
+    #[test]
+    fn truncated_options() {
+        let mut packet = [0; 600];
+        let len = reply(
+            &[
+                53, 1, 5, 3, 4, 10, 0, 0, 1, 119, 5, 3, b'l', b'a', b'n', 0, 0xff,
+            ],
+            &mut packet,
+        );
+        let mut buffer = [0; 600];
+        for len in 0..len {
+            if let Some(message) = DHCPMessage::decode(&packet[..len], &mut buffer) {
+                message.options.iter().for_each(|_| ());
+            }
+        }
+    }
+
+    #[test]
+    fn more_options_than_a_list_holds() {
+        let mut options = [0; 512];
+        let mut len = 0;
+        let mut push = |option: &[u8]| {
+            options[len..len + option.len()].copy_from_slice(option);
+            len += option.len();
+        };
+        push(&[53, 1, 5]);
+        push(&[54, 4, 10, 0, 0, 1]);
+        push(&[51, 4, 0, 0, 0x0e, 0x10]);
+        push(&[58, 4, 0, 0, 0x07, 0x08]);
+        push(&[59, 4, 0, 0, 0x0c, 0x4e]);
+        push(&[1, 4, 255, 255, 255, 0]);
+        push(&[3, 4, 10, 0, 0, 1]);
+        push(&[6, 8, 10, 0, 0, 53, 10, 0, 0, 54]);
+        push(&[15, 3, b'l', b'a', b'n']);
+        push(&[28, 4, 10, 0, 0, 255]);
+        push(&[42, 4, 10, 0, 0, 123]);
+        push(&[2, 4, 0, 0, 0, 0]);
+        push(&[23, 1, 64]);
+        push(&[26, 2, 0x05, 0xdc]);
+        push(&[37, 1, 64]);
+        push(&[44, 4, 10, 0, 0, 2]);
+        push(&[12, 4, b'h', b'o', b's', b't']);
+        push(&[119, 5, 3, b'l', b'a', b'n', 0]);
+        push(&[121, 5, 0, 10, 0, 0, 254]);
+        push(&[0xff]);
+        let mut packet = [0; 800];
+        let packet_len = reply(&options[..len], &mut packet);
+        let mut buffer = [0; 800];
+        let mut message = DHCPMessage::decode(&packet[..packet_len], &mut buffer).unwrap();
+        assert_eq!(message.server_identifier(), Some([10, 0, 0, 1]));
+        let route = message.classless_static_route().unwrap().iter().next();
+        assert_eq!(route.map(|x| x.gateway), Some([10, 0, 0, 254]));
+        let mut name = [0; 16];
+        let domain = message.domain_search().unwrap().iter().next().unwrap();
+        assert_eq!(domain.to_text(&mut name), Some("lan."));
+
+        // too many to change , nothing is lost.
+        assert!(message.remove_option(54).is_none());
+        assert!(message.insert_option(Options::RapidCommit).is_none());
+        assert_eq!(message.server_identifier(), Some([10, 0, 0, 1]));
+        assert_eq!(message.options.iter().count(), 20);
+
+        // a full list takes no more.
+        let options = [Options::DefaultIpTTL(64); MAX_OPTIONS - 2];
+        let mut message = DHCPMessage {
+            options: option_list(&options),
+            ..message
+        };
+        assert!(message.insert_option(Options::RapidCommit).is_some());
+        assert!(message.insert_option(Options::RapidCommit).is_none());
+        assert_eq!(message.options.iter().count(), MAX_OPTIONS);
+    }
+
+    #[test]
+    fn change_received_message() {
+        let mut packet = [0; 600];
+        let len = reply(
+            &[53, 1, 5, 54, 4, 10, 0, 0, 1, 150, 2, 1, 2, 0xff],
+            &mut packet,
+        );
+        let mut buffer = [0; 900];
+        let mut message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        assert!(message.remove_option(54).is_some());
+        assert_eq!(message.server_identifier(), None);
+        assert_eq!(message.message_type(), Some(MessageTy::Ack));
+        assert!(message.insert_option(Options::RapidCommit).is_some());
+        assert!(message.has_rapid_commit());
+    }
+
+    #[test]
+    fn path_mtu_plateau_table_odd_length() {
+        assert!(OptionDecoder::decode(25, &[0x05, 0xdc, 0x02]).is_none());
+    }
+}
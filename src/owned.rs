@@ -0,0 +1,225 @@
+//! owned messages , for keeping a decoded message past the receive buffer.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{
+    option_list, DHCPMessage, EncapsulatedOptions, HType, MessageOptions, OptionDecoder,
+    OptionEncoder, Options, HEADER_LEN, MAGIC_COOKIE, MAX_OPTIONS, MIN_MESSAGE_LEN,
+};
+
+/// one option , all its instances joined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OptionOwned {
+    pub tag: u8,
+    pub data: Vec<u8>,
+}
+
+impl OptionOwned {
+    /// typed view , None for unknown or malformed options.
+    pub fn option(&self) -> Option<Options<'_>> {
+        OptionDecoder::decode(self.tag, &self.data)
+    }
+
+    /// None for Pad , End and options which can not be encoded.
+    fn from_option(option: &Options) -> Option<Self> {
+        if matches!(option, Options::Pad | Options::End) {
+            return None;
+        }
+        // the value may be split in several instances , join them again.
+        let mut buffer = vec![0; 512];
+        let len = loop {
+            let len = OptionEncoder::new(&mut buffer).encode(option).next_pos;
+            match len {
+                0 if buffer.len() < MAX_VALUE_LEN => buffer.resize(buffer.len() * 2, 0),
+                0 => return None,
+                len => break len,
+            }
+        };
+        let mut data = Vec::new();
+        EncapsulatedOptions::new(&buffer[..len]).for_each(|(_, x)| data.extend_from_slice(x));
+        Some(Self {
+            tag: option.tag_number(),
+            data,
+        })
+    }
+}
+
+/// larger values of a typed option are not kept.
+const MAX_VALUE_LEN: usize = 128 * 1024;
+
+#[derive(Clone, Debug)]
+pub struct DHCPMessageOwned {
+    pub op: crate::Op,
+    pub htype: HType,
+    pub hlen: u8,
+    pub hops: u8,
+    pub xid: u32,
+    pub secs: u16,
+    pub flags: bool,
+    pub ciaddr: [u8; 4],
+    pub yiaddr: [u8; 4],
+    pub siaddr: [u8; 4],
+    pub giaddr: [u8; 4],
+    pub chaddr: [u8; 16],
+    pub sname: [u8; 64],
+    pub file: [u8; 128],
+    /// in order of first appearance , without Pad and End.
+    pub options: Vec<OptionOwned>,
+}
+
+impl DHCPMessageOwned {
+    /// decode a received message of any size , see [`DHCPMessage::decode`].
+    pub fn decode(packet: &[u8]) -> Option<Self> {
+        // joining adds at most one byte per instance , plus the magic cookie
+        // and End. the file and sname fields may be overloaded.
+        let area = packet.len().saturating_sub(HEADER_LEN) + 64 + 128;
+        let mut buffer = vec![0; area * 3 / 2 + 5];
+        let message = DHCPMessage::decode(packet, &mut buffer)?;
+        Some(Self::from(&message))
+    }
+
+    /// the first option with `tag`.
+    pub fn get(&self, tag: u8) -> Option<&OptionOwned> {
+        self.options.iter().find(|x| x.tag == tag)
+    }
+
+    /// borrowed view , options past [`MAX_OPTIONS`] are left out.
+    pub fn as_message(&self) -> DHCPMessage<'_> {
+        let mut options = [Options::Pad; MAX_OPTIONS - 1];
+        self.options
+            .iter()
+            .filter_map(OptionOwned::option)
+            .zip(options.iter_mut())
+            .for_each(|(option, cell)| *cell = option);
+        let len = options
+            .iter()
+            .position(|x| matches!(x, Options::Pad))
+            .unwrap_or(options.len());
+        DHCPMessage {
+            options: option_list(&options[..len]),
+            ..self.header()
+        }
+    }
+
+    /// the fixed part , without options.
+    fn header(&self) -> DHCPMessage<'_> {
+        DHCPMessage {
+            op: self.op,
+            htype: self.htype,
+            hlen: self.hlen,
+            hops: self.hops,
+            xid: self.xid,
+            secs: self.secs,
+            flags: self.flags,
+            ciaddr: self.ciaddr,
+            yiaddr: self.yiaddr,
+            siaddr: self.siaddr,
+            giaddr: self.giaddr,
+            chaddr: self.chaddr,
+            sname: self.sname,
+            file: self.file,
+            options: option_list(&[]),
+        }
+    }
+
+    /// encode in a new buffer , see [`DHCPMessage::encode`].
+    ///
+    /// every option is written as it is , unknown ones included , values
+    /// longer than 255 bytes split in several instances (RFC 3396).
+    pub fn encode(&self) -> Vec<u8> {
+        let options = self.options.iter().filter(|x| !matches!(x.tag, 0 | 0xff));
+        let len = HEADER_LEN
+            + MAGIC_COOKIE.len()
+            + options
+                .clone()
+                .map(|x| x.data.len() + 2 * x.data.len().div_ceil(255).max(1))
+                .sum::<usize>()
+            + 1;
+        let mut buffer = vec![0; HEADER_LEN];
+        self.header().encode_header(&mut buffer);
+        buffer.reserve(len.max(MIN_MESSAGE_LEN) - HEADER_LEN);
+        buffer.extend_from_slice(&MAGIC_COOKIE);
+        for option in options {
+            if option.data.is_empty() {
+                buffer.extend_from_slice(&[option.tag, 0]);
+            }
+            for part in option.data.chunks(255) {
+                buffer.extend_from_slice(&[option.tag, part.len() as u8]);
+                buffer.extend_from_slice(part);
+            }
+        }
+        buffer.push(0xff);
+        buffer.resize(len.max(MIN_MESSAGE_LEN), 0);
+        buffer
+    }
+}
+
+impl From<&DHCPMessage<'_>> for DHCPMessageOwned {
+    fn from(message: &DHCPMessage<'_>) -> Self {
+        let options = match &message.options {
+            MessageOptions::Joined(x) => x
+                .values()
+                .map(|(tag, data)| OptionOwned {
+                    tag,
+                    data: data.to_vec(),
+                })
+                .collect(),
+            MessageOptions::List(x) => x.iter().filter_map(OptionOwned::from_option).collect(),
+        };
+        Self {
+            op: message.op,
+            htype: message.htype,
+            hlen: message.hlen,
+            hops: message.hops,
+            xid: message.xid,
+            secs: message.secs,
+            flags: message.flags,
+            ciaddr: message.ciaddr,
+            yiaddr: message.yiaddr,
+            siaddr: message.siaddr,
+            giaddr: message.giaddr,
+            chaddr: message.chaddr,
+            sname: message.sname,
+            file: message.file,
+            options,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::reply;
+    use crate::HwAddress;
+
+    #[test]
+    fn round_trip() {
+        let mut options = vec![53, 1, 5, 150, 3, 1, 2, 3, 43, 255];
+        options.extend((0..255).map(|x| x as u8));
+        options.extend_from_slice(&[43, 45]);
+        options.extend((255..300).map(|x| x as u8));
+        options.push(0xff);
+        let mut packet = [0; 600];
+        let len = reply(&options, &mut packet);
+        let message = DHCPMessageOwned::decode(&packet[..len]).unwrap();
+        assert_eq!(message.options.len(), 3);
+        assert_eq!(message.get(150).unwrap().data, [1, 2, 3]);
+        let vendor = &message.get(43).unwrap().data;
+        assert!(vendor.iter().copied().eq((0..300).map(|x| x as u8)));
+        // unknown option and split value as received.
+        assert_eq!(message.encode(), packet[..len]);
+    }
+
+    #[test]
+    fn from_list() {
+        let message = DHCPMessage::new_discover(
+            0x1234,
+            HwAddress::Ethernet(&[2, 0, 0, 0, 0, 1]),
+            HType::Ethernet,
+            &[1, 3, 6],
+        );
+        let mut buffer = [0; 600];
+        let len = message.encode(&mut buffer).unwrap();
+        assert_eq!(DHCPMessageOwned::from(&message).encode(), buffer[..len]);
+    }
+}
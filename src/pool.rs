@@ -0,0 +1,409 @@
+//! address pool and lease database for [`crate::server`].
+//!
+//! fixed capacity , no allocation. time is seconds from any epoch the caller likes.
+use crate::{DHCPMessage, Options};
+
+/// longer client identifiers are truncated.
+pub const CLIENT_KEY_LEN: usize = 32;
+
+/// client identifier (option 61) , or hardware type and chaddr when absent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientKey {
+    len: u8,
+    bytes: [u8; CLIENT_KEY_LEN],
+}
+
+impl ClientKey {
+    pub fn new(ty: u8, id: &[u8]) -> Self {
+        let mut bytes = [0; CLIENT_KEY_LEN];
+        let len = (id.len() + 1).min(CLIENT_KEY_LEN);
+        bytes[0] = ty;
+        bytes[1..len].copy_from_slice(&id[0..len - 1]);
+        Self {
+            len: len as u8,
+            bytes,
+        }
+    }
+
+    pub fn from_message(message: &DHCPMessage) -> Self {
+        message
+            .options
+            .iter()
+            .find_map(|x| match x {
+                Options::ClientIdentifier(ty, id) => Some(Self::new(ty, id)),
+                _ => None,
+            })
+            .unwrap_or_else(|| {
+                let (htype, _) = message.htype.into();
+                Self::new(htype, message.chaddr())
+            })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[0..self.len as usize]
+    }
+}
+
+/// fixed address for one client.
+#[derive(Clone, Copy, Debug)]
+pub struct Reservation {
+    pub client: ClientKey,
+    pub ip_addr: [u8; 4],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Binding {
+    Offered,
+    Leased,
+    /// released or expired , kept to give the same address next time.
+    Released,
+    /// quarantined , no client.
+    Declined,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    client: ClientKey,
+    ip_addr: u32,
+    binding: Binding,
+    expires: u32,
+}
+
+impl Entry {
+    fn is_active(&self, now: u32) -> bool {
+        match self.binding {
+            Binding::Released => false,
+            _ => now < self.expires,
+        }
+    }
+}
+
+pub struct LeasePool<'a, const N: usize> {
+    start: u32,
+    end: u32,
+    reservations: &'a [Reservation],
+    offer_timeout: u32,
+    quarantine_time: u32,
+    entries: [Option<Entry>; N],
+}
+
+impl<'a, const N: usize> LeasePool<'a, N> {
+    /// addresses from `start` to `end` , both inclusive.
+    pub fn new(start: [u8; 4], end: [u8; 4], reservations: &'a [Reservation]) -> Self {
+        Self {
+            start: u32::from_be_bytes(start),
+            end: u32::from_be_bytes(end),
+            reservations,
+            offer_timeout: 60,
+            quarantine_time: 3600,
+            entries: [None; N],
+        }
+    }
+
+    /// how long an offered address is held for the client. default 60 seconds.
+    pub fn with_offer_timeout(mut self, offer_timeout: u32) -> Self {
+        self.offer_timeout = offer_timeout;
+        self
+    }
+
+    /// how long a declined address is not given to anyone. default 1 hour.
+    pub fn with_quarantine_time(mut self, quarantine_time: u32) -> Self {
+        self.quarantine_time = quarantine_time;
+        self
+    }
+
+    /// pick an address for DISCOVER and hold it.
+    ///
+    /// tries the reservation , the previous address of the client , the requested
+    /// address and then the first free one.
+    pub fn offer(
+        &mut self,
+        client: &ClientKey,
+        requested_ip_addr: Option<[u8; 4]>,
+        now: u32,
+    ) -> Option<[u8; 4]> {
+        let ip_addr = match self.reserved(client) {
+            Some(ip_addr) => Some(ip_addr).filter(|x| self.is_available(*x, client, now)),
+            None => self
+                .find(client)
+                .map(|x| self.entries[x].unwrap().ip_addr)
+                .filter(|x| self.in_range(*x) && self.is_available(*x, client, now))
+                .or_else(|| {
+                    requested_ip_addr
+                        .map(u32::from_be_bytes)
+                        .filter(|x| self.in_range(*x) && self.is_available(*x, client, now))
+                })
+                .or_else(|| {
+                    // the entries and reservations take at most this many
+                    // addresses , so one more is enough however large the range.
+                    let candidates = (self.start..=self.end).take(N + self.reservations.len() + 1);
+                    // keep the memory of other clients as long as possible.
+                    candidates
+                        .clone()
+                        .find(|x| self.is_unused(*x) && self.is_available(*x, client, now))
+                        .or_else(|| {
+                            candidates
+                                .clone()
+                                .find(|x| self.is_available(*x, client, now))
+                        })
+                }),
+        }?;
+        self.hold(client, ip_addr, Binding::Offered, self.offer_timeout, now)?;
+        Some(ip_addr.to_be_bytes())
+    }
+
+    /// bind the address for REQUEST. false means NAK.
+    pub fn commit(
+        &mut self,
+        client: &ClientKey,
+        ip_addr: [u8; 4],
+        lease_time: u32,
+        now: u32,
+    ) -> bool {
+        let ip_addr = u32::from_be_bytes(ip_addr);
+        let valid = match self.reserved(client) {
+            Some(reserved) => reserved == ip_addr,
+            None => self.in_range(ip_addr),
+        };
+        valid
+            && self.is_available(ip_addr, client, now)
+            && self
+                .hold(client, ip_addr, Binding::Leased, lease_time, now)
+                .is_some()
+    }
+
+    /// the client selected another server.
+    pub fn cancel_offer(&mut self, client: &ClientKey) {
+        if let Some(index) = self.find(client) {
+            let entry = self.entries[index].as_mut().unwrap();
+            if entry.binding == Binding::Offered {
+                entry.binding = Binding::Released;
+            }
+        }
+    }
+
+    pub fn release(&mut self, client: &ClientKey, ip_addr: [u8; 4]) {
+        if let Some(index) = self.find(client) {
+            let entry = self.entries[index].as_mut().unwrap();
+            if entry.ip_addr == u32::from_be_bytes(ip_addr) {
+                entry.binding = Binding::Released;
+            }
+        }
+    }
+
+    /// the address is in use by someone else. it is not given out for a while.
+    pub fn decline(&mut self, client: &ClientKey, ip_addr: [u8; 4], now: u32) {
+        let ip_addr = u32::from_be_bytes(ip_addr);
+        let index = match self.find(client) {
+            Some(index) if self.entries[index].unwrap().ip_addr == ip_addr => index,
+            _ => return,
+        };
+        self.entries[index] = Some(Entry {
+            client: ClientKey::new(0, &[]),
+            ip_addr,
+            binding: Binding::Declined,
+            expires: now.saturating_add(self.quarantine_time),
+        });
+    }
+
+    /// address bound to the client , if the lease is still valid.
+    pub fn lease(&self, client: &ClientKey, now: u32) -> Option<[u8; 4]> {
+        let entry = self.entries[self.find(client)?]?;
+        (entry.binding == Binding::Leased && entry.is_active(now))
+            .then(|| entry.ip_addr.to_be_bytes())
+    }
+
+    fn in_range(&self, ip_addr: u32) -> bool {
+        (self.start..=self.end).contains(&ip_addr)
+    }
+
+    fn reserved(&self, client: &ClientKey) -> Option<u32> {
+        self.reservations
+            .iter()
+            .find(|x| x.client == *client)
+            .map(|x| u32::from_be_bytes(x.ip_addr))
+    }
+
+    fn find(&self, client: &ClientKey) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|x| x.is_some_and(|x| x.binding != Binding::Declined && x.client == *client))
+    }
+
+    fn is_unused(&self, ip_addr: u32) -> bool {
+        !self
+            .entries
+            .iter()
+            .any(|x| x.is_some_and(|x| x.ip_addr == ip_addr))
+    }
+
+    fn is_available(&self, ip_addr: u32, client: &ClientKey, now: u32) -> bool {
+        let reserved_for_other = self
+            .reservations
+            .iter()
+            .any(|x| u32::from_be_bytes(x.ip_addr) == ip_addr && x.client != *client);
+        let in_use = self.entries.iter().any(|x| {
+            x.is_some_and(|x| x.ip_addr == ip_addr && x.client != *client && x.is_active(now))
+        });
+        !reserved_for_other && !in_use
+    }
+
+    fn hold(
+        &mut self,
+        client: &ClientKey,
+        ip_addr: u32,
+        binding: Binding,
+        duration: u32,
+        now: u32,
+    ) -> Option<()> {
+        // forget whoever had the address before.
+        self.entries.iter_mut().for_each(|x| {
+            if x.is_some_and(|x| x.ip_addr == ip_addr && x.client != *client) {
+                *x = None;
+            }
+        });
+        let index = self.find(client).or_else(|| self.free_slot(now))?;
+        self.entries[index] = Some(Entry {
+            client: *client,
+            ip_addr,
+            binding,
+            expires: now.saturating_add(duration),
+        });
+        Some(())
+    }
+
+    /// empty slot , or the one which expired first.
+    fn free_slot(&self, now: u32) -> Option<usize> {
+        self.entries.iter().position(|x| x.is_none()).or_else(|| {
+            self.entries
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| x.is_some_and(|x| !x.is_active(now)))
+                .min_by_key(|(_, x)| x.unwrap().expires)
+                .map(|(index, _)| index)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(id: u8) -> ClientKey {
+        ClientKey::new(1, &[2, 0, 0, 0, 0, id])
+    }
+
+    #[test]
+    fn reservation_wins() {
+        let reservations = [Reservation {
+            client: client(1),
+            ip_addr: [10, 0, 0, 5],
+        }];
+        let mut pool: LeasePool<4> = LeasePool::new([10, 0, 0, 1], [10, 0, 0, 9], &reservations);
+        assert_eq!(
+            pool.offer(&client(2), Some([10, 0, 0, 5]), 0),
+            Some([10, 0, 0, 1])
+        );
+        assert!(!pool.commit(&client(2), [10, 0, 0, 5], 600, 0));
+        assert_eq!(
+            pool.offer(&client(1), Some([10, 0, 0, 3]), 0),
+            Some([10, 0, 0, 5])
+        );
+        assert!(!pool.commit(&client(1), [10, 0, 0, 3], 600, 0));
+        assert!(pool.commit(&client(1), [10, 0, 0, 5], 600, 0));
+        assert_eq!(pool.lease(&client(1), 0), Some([10, 0, 0, 5]));
+    }
+
+    #[test]
+    fn previous_address() {
+        let mut pool: LeasePool<4> = LeasePool::new([10, 0, 0, 1], [10, 0, 0, 9], &[]);
+        assert_eq!(pool.offer(&client(1), None, 0), Some([10, 0, 0, 1]));
+        assert!(pool.commit(&client(1), [10, 0, 0, 1], 600, 0));
+        pool.release(&client(1), [10, 0, 0, 1]);
+        assert_eq!(pool.lease(&client(1), 0), None);
+        // the released address is remembered for its client.
+        assert_eq!(pool.offer(&client(2), None, 10), Some([10, 0, 0, 2]));
+        assert_eq!(
+            pool.offer(&client(1), Some([10, 0, 0, 7]), 10),
+            Some([10, 0, 0, 1])
+        );
+    }
+
+    #[test]
+    fn requested_address() {
+        let mut pool: LeasePool<4> = LeasePool::new([10, 0, 0, 1], [10, 0, 0, 9], &[]);
+        assert_eq!(
+            pool.offer(&client(1), Some([10, 0, 0, 7]), 0),
+            Some([10, 0, 0, 7])
+        );
+        // taken.
+        assert_eq!(
+            pool.offer(&client(2), Some([10, 0, 0, 7]), 0),
+            Some([10, 0, 0, 1])
+        );
+        // out of range.
+        assert_eq!(
+            pool.offer(&client(3), Some([10, 0, 1, 7]), 0),
+            Some([10, 0, 0, 2])
+        );
+        assert!(!pool.commit(&client(3), [10, 0, 1, 7], 600, 0));
+    }
+
+    #[test]
+    fn offer_expires() {
+        let mut pool: LeasePool<4> =
+            LeasePool::new([10, 0, 0, 1], [10, 0, 0, 9], &[]).with_offer_timeout(30);
+        assert_eq!(pool.offer(&client(1), None, 0), Some([10, 0, 0, 1]));
+        assert_eq!(
+            pool.offer(&client(2), Some([10, 0, 0, 1]), 29),
+            Some([10, 0, 0, 2])
+        );
+        assert_eq!(
+            pool.offer(&client(3), Some([10, 0, 0, 1]), 30),
+            Some([10, 0, 0, 1])
+        );
+        assert!(!pool.commit(&client(1), [10, 0, 0, 1], 600, 30));
+    }
+
+    #[test]
+    fn declined_address_is_quarantined() {
+        let mut pool: LeasePool<4> =
+            LeasePool::new([10, 0, 0, 1], [10, 0, 0, 9], &[]).with_quarantine_time(100);
+        assert_eq!(pool.offer(&client(1), None, 0), Some([10, 0, 0, 1]));
+        assert!(pool.commit(&client(1), [10, 0, 0, 1], 600, 0));
+        pool.decline(&client(1), [10, 0, 0, 1], 0);
+        assert_eq!(pool.lease(&client(1), 0), None);
+        assert_eq!(pool.offer(&client(1), None, 0), Some([10, 0, 0, 2]));
+        assert_eq!(
+            pool.offer(&client(2), Some([10, 0, 0, 1]), 99),
+            Some([10, 0, 0, 3])
+        );
+        assert!(!pool.commit(&client(2), [10, 0, 0, 1], 600, 99));
+        assert_eq!(
+            pool.offer(&client(3), Some([10, 0, 0, 1]), 100),
+            Some([10, 0, 0, 1])
+        );
+    }
+
+    #[test]
+    fn exhausted() {
+        let mut pool: LeasePool<4> = LeasePool::new([10, 0, 0, 1], [10, 0, 0, 2], &[]);
+        assert!(pool.commit(&client(1), [10, 0, 0, 1], 600, 0));
+        assert!(pool.commit(&client(2), [10, 0, 0, 2], 600, 0));
+        assert_eq!(pool.offer(&client(3), None, 0), None);
+        assert!(!pool.commit(&client(3), [10, 0, 0, 1], 600, 0));
+        // expired leases are given out again.
+        assert_eq!(pool.offer(&client(3), None, 600), Some([10, 0, 0, 1]));
+    }
+
+    #[test]
+    fn large_range() {
+        let mut pool: LeasePool<4> = LeasePool::new([10, 0, 0, 0], [10, 255, 255, 255], &[]);
+        for id in 0..4 {
+            assert_eq!(pool.offer(&client(id), None, 0), Some([10, 0, 0, id]));
+            assert!(pool.commit(&client(id), [10, 0, 0, id], 600, 0));
+        }
+        // the table is full , no slot for a fifth client.
+        assert_eq!(pool.offer(&client(4), None, 0), None);
+    }
+}
@@ -0,0 +1,367 @@
+//! network boot , PXE 2.1 and RFC 4578.
+use crate::{DHCPMessage, Encapsulated, EncapsulatedIter, HType, HwAddress, MessageTy, Options};
+
+/// identity of a PXE client.
+#[derive(Clone, Copy, Debug)]
+pub struct PxeClient<'a> {
+    /// client system architecture (option 93) , 0 for x86 BIOS , 7 for x64 UEFI.
+    pub architecture: u16,
+    /// UNDI version (option 94).
+    pub undi_major: u8,
+    pub undi_minor: u8,
+    /// machine GUID (option 97).
+    pub machine_id: &'a [u8; 16],
+    /// made by [`PxeClient::vendor_class`].
+    pub vendor_class: &'a str,
+}
+
+impl<'a> PxeClient<'a> {
+    /// "PXEClient:Arch:xxxxx:UNDI:yyyzzz" for option 60.
+    pub fn vendor_class(
+        architecture: u16,
+        undi_major: u8,
+        undi_minor: u8,
+        buffer: &'a mut [u8; 32],
+    ) -> &'a str {
+        fn digits(buffer: &mut [u8], mut x: u16) {
+            buffer.iter_mut().rev().for_each(|digit| {
+                *digit = b'0' + (x % 10) as u8;
+                x /= 10;
+            });
+        }
+        buffer[0..15].copy_from_slice(b"PXEClient:Arch:");
+        digits(&mut buffer[15..20], architecture);
+        buffer[20..26].copy_from_slice(b":UNDI:");
+        digits(&mut buffer[26..29], undi_major as u16);
+        digits(&mut buffer[29..32], undi_minor as u16);
+        // only ascii was written.
+        core::str::from_utf8(buffer).unwrap_or("PXEClient")
+    }
+}
+
+impl<'a> DHCPMessage<'a> {
+    /// DISCOVER with the options a PXE client sends.
+    ///
+    /// `parameter_request_list` should include 43 , 60 , 66 and 67.
+    pub fn new_pxe_discover(
+        transaction_id: u32,
+        hardware_address: HwAddress<'a>,
+        hw_type: HType,
+        parameter_request_list: &'a [u8],
+        client: &PxeClient<'a>,
+    ) -> Self {
+        Self::new_boot_request(
+            transaction_id,
+            hardware_address,
+            hw_type,
+            &[
+                Options::DHCPMessageType(MessageTy::Discover),
+                Options::ClientSystemArchitecture(client.architecture),
+                Options::ClientNetworkInterfaceIdentifier(1, client.undi_major, client.undi_minor),
+                Options::ClientMachineIdentifier(0, client.machine_id),
+                Options::VendorClassIdentifier(client.vendor_class),
+                Options::ParameterRequestList(parameter_request_list),
+            ],
+        )
+    }
+}
+
+/// where to load the boot image from.
+#[derive(Clone, Copy, Debug)]
+pub struct BootInfo<'b> {
+    /// siaddr.
+    pub next_server: [u8; 4],
+    /// option 67 , or file.
+    pub boot_file: Option<&'b str>,
+    /// option 66 , or sname.
+    pub tftp_server_name: Option<&'b str>,
+}
+
+impl<'b> BootInfo<'b> {
+    pub fn from_reply(message: &'b DHCPMessage) -> Self {
+        let mut boot_file = None;
+        let mut tftp_server_name = None;
+        for option in message.options.iter() {
+            match option {
+                Options::BootFileName(x) => boot_file = Some(x),
+                Options::TFTPServerName(x) => tftp_server_name = Some(x),
+                _ => {}
+            }
+        }
+        Self {
+            next_server: message.siaddr,
+            boot_file: boot_file.or_else(|| message.boot_file()),
+            tftp_server_name: tftp_server_name.or_else(|| message.server_name()),
+        }
+    }
+}
+
+/// PXE sub-options of option 43 , when the server answered with "PXEClient" in option 60.
+pub fn vendor_options<'a>(message: &DHCPMessage<'a>) -> Option<PxeVendorOptions<'a>> {
+    let is_pxe = message
+        .options
+        .iter()
+        .any(|x| matches!(x, Options::VendorClassIdentifier(x) if x.starts_with("PXEClient")));
+    if !is_pxe {
+        return None;
+    }
+    message
+        .vendor_specific_information()
+        .map(PxeVendorOptions::new)
+}
+
+pub struct PxeVendorOptions<'a> {
+    options: EncapsulatedIter<'a>,
+}
+
+impl<'a> PxeVendorOptions<'a> {
+    pub fn new(information: Encapsulated<'a>) -> Self {
+        Self {
+            options: information.iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for PxeVendorOptions<'a> {
+    type Item = PxeVendorOption<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.options
+            .next()
+            .map(|(code, data)| PxeVendorOption::decode(code, data))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum PxeVendorOption<'a> {
+    /// bit 0 : no broadcast discovery , bit 1 : no multicast discovery ,
+    /// bit 2 : only servers in BootServers , bit 3 : download the boot file directly.
+    DiscoveryControl(u8),
+    DiscoveryMulticastAddress([u8; 4]),
+    BootServers(BootServers<'a>),
+    BootMenu(BootMenu<'a>),
+    /// timeout in seconds and prompt.
+    MenuPrompt(u8, &'a str),
+    /// boot server type and layer.
+    BootItem(u16, u16),
+    Unknown(u8, &'a [u8]),
+}
+
+impl<'a> PxeVendorOption<'a> {
+    fn decode(code: u8, data: &'a [u8]) -> Self {
+        match (code, data) {
+            (6, [x]) => PxeVendorOption::DiscoveryControl(*x),
+            (7, [a, b, c, d]) => PxeVendorOption::DiscoveryMulticastAddress([*a, *b, *c, *d]),
+            (8, _) => PxeVendorOption::BootServers(BootServers { bytes: data }),
+            (9, _) => PxeVendorOption::BootMenu(BootMenu { bytes: data }),
+            (10, [timeout, prompt @ ..]) => match core::str::from_utf8(prompt) {
+                Ok(prompt) => PxeVendorOption::MenuPrompt(*timeout, prompt),
+                Err(_) => PxeVendorOption::Unknown(code, data),
+            },
+            (71, [a, b, c, d]) => PxeVendorOption::BootItem(
+                u16::from_be_bytes([*a, *b]),
+                u16::from_be_bytes([*c, *d]),
+            ),
+            _ => PxeVendorOption::Unknown(code, data),
+        }
+    }
+}
+
+/// (boot server type , addresses).
+#[derive(Clone, Copy, Debug)]
+pub struct BootServers<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for BootServers<'a> {
+    type Item = (u16, &'a [[u8; 4]]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let [a, b, count, rest @ ..] = self.bytes else {
+            return None;
+        };
+        let (addrs, _) = rest.as_chunks::<4>();
+        let Some(addrs) = addrs.get(..*count as usize) else {
+            self.bytes = &[];
+            return None;
+        };
+        self.bytes = &rest[addrs.len() * 4..];
+        Some((u16::from_be_bytes([*a, *b]), addrs))
+    }
+}
+
+/// (boot server type , description).
+#[derive(Clone, Copy, Debug)]
+pub struct BootMenu<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for BootMenu<'a> {
+    type Item = (u16, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let [a, b, len, rest @ ..] = self.bytes else {
+            return None;
+        };
+        let description = rest
+            .get(..*len as usize)
+            .and_then(|x| core::str::from_utf8(x).ok());
+        let Some(description) = description else {
+            self.bytes = &[];
+            return None;
+        };
+        self.bytes = &rest[description.len()..];
+        Some((u16::from_be_bytes([*a, *b]), description))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::reply;
+    use crate::{HEADER_LEN, MAGIC_COOKIE};
+
+    /// `parts` one after the other in `buffer`.
+    fn join<'b>(parts: &[&[u8]], buffer: &'b mut [u8]) -> &'b [u8] {
+        let len = parts.iter().fold(0, |len, part| {
+            buffer[len..len + part.len()].copy_from_slice(part);
+            len + part.len()
+        });
+        &buffer[..len]
+    }
+
+    #[test]
+    fn vendor_class() {
+        let mut buffer = [0; 32];
+        assert_eq!(
+            PxeClient::vendor_class(0, 2, 1, &mut buffer),
+            "PXEClient:Arch:00000:UNDI:002001"
+        );
+        assert_eq!(
+            PxeClient::vendor_class(65535, 3, 16, &mut buffer),
+            "PXEClient:Arch:65535:UNDI:003016"
+        );
+    }
+
+    #[test]
+    fn pxe_discover() {
+        let mut vendor_class = [0; 32];
+        let client = PxeClient {
+            architecture: 7,
+            undi_major: 3,
+            undi_minor: 16,
+            machine_id: &[0x11; 16],
+            vendor_class: PxeClient::vendor_class(7, 3, 16, &mut vendor_class),
+        };
+        let message = DHCPMessage::new_pxe_discover(
+            0x1234,
+            HwAddress::Ethernet(&[2, 0, 0, 0, 0, 1]),
+            HType::Ethernet,
+            &[43, 60, 66, 67],
+            &client,
+        );
+        let mut buffer = [0; 600];
+        let len = message.encode(&mut buffer).unwrap();
+        assert_eq!(buffer[..4], [1, 1, 6, 0]);
+        assert_eq!(buffer[4..8], 0x1234u32.to_be_bytes());
+        // broadcast flag.
+        assert_eq!(buffer[10], 0x80);
+        assert_eq!(buffer[28..34], [2, 0, 0, 0, 0, 1]);
+        assert_eq!(buffer[HEADER_LEN..HEADER_LEN + 4], MAGIC_COOKIE);
+        let mut expected = [0; 80];
+        let expected = join(
+            &[
+                &[53, 1, 1, 93, 2, 0, 7, 94, 3, 1, 3, 16, 97, 17, 0],
+                &[0x11; 16],
+                &[60, 32],
+                b"PXEClient:Arch:00007:UNDI:003016",
+                &[55, 4, 43, 60, 66, 67, 0xff],
+            ],
+            &mut expected,
+        );
+        let options = &buffer[HEADER_LEN + 4..len];
+        assert_eq!(options[..expected.len()], *expected);
+    }
+
+    #[test]
+    fn boot_info() {
+        let mut packet = [0; 600];
+        let len = reply(&[0xff], &mut packet);
+        packet[20..24].copy_from_slice(&[10, 0, 0, 2]);
+        packet[44..48].copy_from_slice(b"tftp");
+        packet[108..116].copy_from_slice(b"pxelinux");
+        let mut buffer = [0; 900];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let info = BootInfo::from_reply(&message);
+        assert_eq!(info.next_server, [10, 0, 0, 2]);
+        assert_eq!(info.tftp_server_name, Some("tftp"));
+        assert_eq!(info.boot_file, Some("pxelinux"));
+        // options 66 and 67 win over sname and file.
+        let mut options = [0; 32];
+        let options = join(
+            &[&[66, 5], b"boot1", &[67, 8], b"ipxe.efi", &[0xff]],
+            &mut options,
+        );
+        let len = reply(options, &mut packet);
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let info = BootInfo::from_reply(&message);
+        assert_eq!(info.tftp_server_name, Some("boot1"));
+        assert_eq!(info.boot_file, Some("ipxe.efi"));
+    }
+
+    #[test]
+    fn pxe_vendor_options() {
+        let mut options = [0; 64];
+        let options = join(
+            &[
+                &[60, 9],
+                b"PXEClient",
+                &[43, 40],
+                // discovery control , boot servers , boot menu , prompt , boot item.
+                &[6, 1, 3],
+                &[8, 7, 0x80, 0x00, 1, 10, 0, 0, 2],
+                &[9, 8, 0x80, 0x00, 5],
+                b"local",
+                &[10, 4, 5],
+                b"go?",
+                &[71, 4, 0x80, 0x00, 0, 0],
+                // truncated.
+                &[12, 9, 1, 2, 3, 4],
+                &[0xff],
+            ],
+            &mut options,
+        );
+        let mut packet = [0; 600];
+        let len = reply(options, &mut packet);
+        let mut buffer = [0; 900];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let mut options = vendor_options(&message).unwrap();
+        assert!(matches!(
+            options.next(),
+            Some(PxeVendorOption::DiscoveryControl(3))
+        ));
+        let Some(PxeVendorOption::BootServers(mut servers)) = options.next() else {
+            panic!();
+        };
+        assert_eq!(servers.next(), Some((0x8000, &[[10, 0, 0, 2]][..])));
+        assert_eq!(servers.next(), None);
+        let Some(PxeVendorOption::BootMenu(mut menu)) = options.next() else {
+            panic!();
+        };
+        assert_eq!(menu.next(), Some((0x8000, "local")));
+        assert_eq!(menu.next(), None);
+        assert!(matches!(
+            options.next(),
+            Some(PxeVendorOption::MenuPrompt(5, "go?"))
+        ));
+        assert!(matches!(
+            options.next(),
+            Some(PxeVendorOption::BootItem(0x8000, 0))
+        ));
+        assert!(options.next().is_none());
+        // not for a PXE client.
+        let len = reply(&[43, 3, 6, 1, 3, 0xff], &mut packet);
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        assert!(vendor_options(&message).is_none());
+    }
+}
@@ -0,0 +1,432 @@
+//! BOOTP relay agent (RFC 1542 4) and Relay Agent Information (RFC 3046).
+//!
+//! works on the received bytes in place , options other than 82 are passed
+//! through untouched.
+use crate::server::Destination;
+use crate::{EncapsulatedOptions, HEADER_LEN, MAGIC_COOKIE};
+
+pub struct Relay<'a> {
+    /// address of the interface facing the clients , put in giaddr.
+    pub ip_addr: [u8; 4],
+    /// servers to forward requests to.
+    pub servers: &'a [[u8; 4]],
+    /// requests with more hops are dropped. RFC 1542 allows up to 16.
+    pub max_hops: u8,
+    /// sub-options of option 82 added to requests , nothing is added when empty.
+    pub agent_information: &'a [RelayAgentSubOption<'a>],
+}
+
+impl<'a> Relay<'a> {
+    pub fn new(ip_addr: [u8; 4], servers: &'a [[u8; 4]]) -> Self {
+        Self {
+            ip_addr,
+            servers,
+            max_hops: 4,
+            agent_information: &[],
+        }
+    }
+
+    pub fn with_max_hops(mut self, max_hops: u8) -> Self {
+        self.max_hops = max_hops;
+        self
+    }
+
+    pub fn with_agent_information(
+        mut self,
+        agent_information: &'a [RelayAgentSubOption<'a>],
+    ) -> Self {
+        self.agent_information = agent_information;
+        self
+    }
+
+    /// prepare a BOOTREQUEST from a client for the servers.
+    ///
+    /// returns where to send it , server port 67. None means drop.
+    pub fn forward_request(&self, packet: &mut [u8]) -> Option<&'a [[u8; 4]]> {
+        if packet.len() < HEADER_LEN || packet[0] != 1 || packet[3] > self.max_hops {
+            return None;
+        }
+        packet[3] = packet[3].saturating_add(1);
+        // keep giaddr of a relay nearer to the client.
+        if packet[24..28] == [0; 4] {
+            packet[24..28].copy_from_slice(&self.ip_addr);
+        }
+        Some(self.servers)
+    }
+
+    /// add option 82 to a request of `len` bytes in `buffer`.
+    ///
+    /// returns the new length , never less than `len` : option 82 takes the
+    /// place of End and what follows. a request which already has option 82
+    /// is left as is. None means the options are malformed or there was no
+    /// room.
+    pub fn insert_agent_information(&self, buffer: &mut [u8], len: usize) -> Option<usize> {
+        if self.agent_information.is_empty() {
+            return Some(len);
+        }
+        let options = options_range(buffer.get(..len)?)?;
+        let end = options.start + options_end(&buffer[options.clone()])?;
+        if find_option(&buffer[options], 82).is_some() {
+            return Some(len);
+        }
+        let information = RelayAgentInformation::SubOptions(self.agent_information);
+        let written = information.encode(buffer.get_mut(end + 2..)?)?;
+        *buffer.get_mut(end + 2 + written)? = 255;
+        buffer[end] = 82;
+        buffer[end + 1] = u8::try_from(written).ok()?;
+        Some(len.max(end + 2 + written + 1))
+    }
+
+    /// where to deliver a BOOTREPLY from a server , client port 68. None means drop.
+    pub fn forward_reply(&self, packet: &[u8]) -> Option<Destination> {
+        if packet.len() < HEADER_LEN || packet[0] != 2 || packet[24..28] != self.ip_addr {
+            return None;
+        }
+        let broadcast = packet[10] & 0x80 != 0;
+        let ciaddr = [packet[12], packet[13], packet[14], packet[15]];
+        let yiaddr = [packet[16], packet[17], packet[18], packet[19]];
+        Some(if ciaddr != [0; 4] {
+            Destination::Unicast(ciaddr)
+        } else if broadcast {
+            Destination::Broadcast
+        } else {
+            Destination::UnicastToHardware(yiaddr)
+        })
+    }
+}
+
+/// remove option 82 from a reply before it goes to the client , every
+/// instance of it.
+///
+/// returns the new length. malformed options are left as is.
+pub fn strip_agent_information(packet: &mut [u8]) -> usize {
+    let mut len = packet.len();
+    let Some(options) = options_range(packet) else {
+        return len;
+    };
+    if options_end(&packet[options.clone()]).is_none() {
+        return len;
+    }
+    while let Some((pos, option_len)) = find_option(&packet[options.start..len], 82) {
+        let pos = options.start + pos;
+        packet.copy_within(pos + option_len..len, pos);
+        len -= option_len;
+    }
+    len
+}
+
+/// options after the magic cookie.
+fn options_range(packet: &[u8]) -> Option<core::ops::Range<usize>> {
+    (packet.get(HEADER_LEN..HEADER_LEN + 4)? == MAGIC_COOKIE)
+        .then_some(HEADER_LEN + 4..packet.len())
+}
+
+/// position of End in `options` , their length without it. None if an
+/// option is truncated.
+fn options_end(options: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    while let Some(&code) = options.get(pos) {
+        match code {
+            255 => return Some(pos),
+            0 => pos += 1,
+            _ => pos += 2 + *options.get(pos + 1)? as usize,
+        }
+    }
+    (pos == options.len()).then_some(pos)
+}
+
+/// position and whole length of the first `tag` before End in `options`.
+fn find_option(options: &[u8], tag: u8) -> Option<(usize, usize)> {
+    let mut pos = 0;
+    while let Some(&code) = options.get(pos) {
+        if code == 255 {
+            return None;
+        }
+        let option_len = match code {
+            0 => 1,
+            _ => 2 + *options.get(pos + 1)? as usize,
+        };
+        if pos + option_len > options.len() {
+            return None;
+        }
+        if code == tag {
+            return Some((pos, option_len));
+        }
+        pos += option_len;
+    }
+    None
+}
+
+/// value of option 82.
+#[derive(Clone, Copy, Debug)]
+pub enum RelayAgentInformation<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    SubOptions(&'a [RelayAgentSubOption<'a>]),
+}
+
+impl<'a> RelayAgentInformation<'a> {
+    pub fn iter(&self) -> RelayAgentSubOptions<'a> {
+        match self {
+            RelayAgentInformation::Bytes(x) => {
+                RelayAgentSubOptions::Bytes(EncapsulatedOptions::new(x))
+            }
+            RelayAgentInformation::SubOptions(x) => RelayAgentSubOptions::SubOptions(x.iter()),
+        }
+    }
+
+    pub fn circuit_id(&self) -> Option<&'a [u8]> {
+        self.iter().find_map(|x| match x {
+            RelayAgentSubOption::CircuitId(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn remote_id(&self) -> Option<&'a [u8]> {
+        self.iter().find_map(|x| match x {
+            RelayAgentSubOption::RemoteId(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// subnet the client is on , when it differs from giaddr.
+    pub fn link_selection(&self) -> Option<[u8; 4]> {
+        self.iter().find_map(|x| match x {
+            RelayAgentSubOption::LinkSelection(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn server_id_override(&self) -> Option<[u8; 4]> {
+        self.iter().find_map(|x| match x {
+            RelayAgentSubOption::ServerIdOverride(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// write the value (without code and length) , returns the length.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            RelayAgentInformation::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                Some(x.len())
+            }
+            RelayAgentInformation::SubOptions(x) => x.iter().try_fold(0, |len, sub_option| {
+                Some(len + sub_option.encode(buffer.get_mut(len..)?)?)
+            }),
+        }
+    }
+}
+
+pub enum RelayAgentSubOptions<'a> {
+    Bytes(EncapsulatedOptions<'a>),
+    SubOptions(core::slice::Iter<'a, RelayAgentSubOption<'a>>),
+}
+
+impl<'a> Iterator for RelayAgentSubOptions<'a> {
+    type Item = RelayAgentSubOption<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            RelayAgentSubOptions::Bytes(x) => x
+                .next()
+                .map(|(code, data)| RelayAgentSubOption::decode(code, data)),
+            RelayAgentSubOptions::SubOptions(x) => x.next().copied(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RelayAgentSubOption<'a> {
+    CircuitId(&'a [u8]),
+    RemoteId(&'a [u8]),
+    /// see RFC 3527 .
+    LinkSelection([u8; 4]),
+    /// see RFC 3993 .
+    SubscriberId(&'a str),
+    /// see RFC 5107 .
+    ServerIdOverride([u8; 4]),
+    /// see RFC 8357 . port of the downstream relay , if not 67.
+    RelaySourcePort(Option<u16>),
+    Unknown(u8, &'a [u8]),
+}
+
+impl<'a> RelayAgentSubOption<'a> {
+    pub fn code(&self) -> u8 {
+        match self {
+            RelayAgentSubOption::CircuitId(_) => 1,
+            RelayAgentSubOption::RemoteId(_) => 2,
+            RelayAgentSubOption::LinkSelection(_) => 5,
+            RelayAgentSubOption::SubscriberId(_) => 6,
+            RelayAgentSubOption::ServerIdOverride(_) => 11,
+            RelayAgentSubOption::RelaySourcePort(_) => 19,
+            RelayAgentSubOption::Unknown(code, _) => *code,
+        }
+    }
+
+    fn decode(code: u8, data: &'a [u8]) -> Self {
+        match (code, data.len()) {
+            (1, _) => RelayAgentSubOption::CircuitId(data),
+            (2, _) => RelayAgentSubOption::RemoteId(data),
+            (5, 4) => RelayAgentSubOption::LinkSelection([data[0], data[1], data[2], data[3]]),
+            (6, _) => match core::str::from_utf8(data) {
+                Ok(x) => RelayAgentSubOption::SubscriberId(x),
+                Err(_) => RelayAgentSubOption::Unknown(code, data),
+            },
+            (11, 4) => RelayAgentSubOption::ServerIdOverride([data[0], data[1], data[2], data[3]]),
+            (19, 0) => RelayAgentSubOption::RelaySourcePort(None),
+            (19, 2) => {
+                RelayAgentSubOption::RelaySourcePort(Some(u16::from_be_bytes([data[0], data[1]])))
+            }
+            _ => RelayAgentSubOption::Unknown(code, data),
+        }
+    }
+
+    /// write code , length and data , returns the length written.
+    fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        let port;
+        let data: &[u8] = match self {
+            RelayAgentSubOption::CircuitId(x)
+            | RelayAgentSubOption::RemoteId(x)
+            | RelayAgentSubOption::Unknown(_, x) => x,
+            RelayAgentSubOption::LinkSelection(x) | RelayAgentSubOption::ServerIdOverride(x) => x,
+            RelayAgentSubOption::SubscriberId(x) => x.as_bytes(),
+            RelayAgentSubOption::RelaySourcePort(x) => {
+                port = x.map(u16::to_be_bytes);
+                port.as_ref().map_or(&[], |x| x)
+            }
+        };
+        let buffer = buffer.get_mut(..2 + data.len())?;
+        buffer[0] = self.code();
+        buffer[1] = u8::try_from(data.len()).ok()?;
+        buffer[2..].copy_from_slice(data);
+        Some(2 + data.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::reply;
+
+    const RELAY: [u8; 4] = [10, 0, 0, 1];
+    const SERVERS: [[u8; 4]; 1] = [[10, 0, 1, 1]];
+
+    #[test]
+    fn forward_request_to_servers() {
+        let relay = Relay::new(RELAY, &SERVERS).with_max_hops(2);
+        let mut packet = [0; 300];
+        let len = reply(&[53, 1, 1, 0xff], &mut packet);
+        packet[0] = 1;
+        assert_eq!(
+            relay.forward_request(&mut packet[..len]),
+            Some(&SERVERS[..])
+        );
+        assert_eq!(packet[3], 1);
+        assert_eq!(packet[24..28], RELAY);
+        // giaddr of the first relay is kept.
+        packet[24..28].copy_from_slice(&[10, 0, 2, 1]);
+        assert!(relay.forward_request(&mut packet[..len]).is_some());
+        assert_eq!(packet[3], 2);
+        assert_eq!(packet[24..28], [10, 0, 2, 1]);
+        assert!(relay.forward_request(&mut packet[..len]).is_some());
+        assert_eq!(packet[3], 3);
+        // past the limit.
+        assert_eq!(relay.forward_request(&mut packet[..len]), None);
+        assert_eq!(packet[3], 3);
+        // not a request.
+        packet[0] = 2;
+        packet[3] = 0;
+        assert_eq!(relay.forward_request(&mut packet[..len]), None);
+    }
+
+    #[test]
+    fn forward_reply_to_client() {
+        let relay = Relay::new(RELAY, &SERVERS);
+        let mut packet = [0; 300];
+        let len = reply(&[53, 1, 5, 0xff], &mut packet);
+        packet[16..20].copy_from_slice(&[10, 0, 0, 100]);
+        // for another relay.
+        assert_eq!(relay.forward_reply(&packet[..len]), None);
+        packet[24..28].copy_from_slice(&RELAY);
+        assert_eq!(
+            relay.forward_reply(&packet[..len]),
+            Some(Destination::UnicastToHardware([10, 0, 0, 100]))
+        );
+        packet[10] = 0x80;
+        assert_eq!(
+            relay.forward_reply(&packet[..len]),
+            Some(Destination::Broadcast)
+        );
+        packet[12..16].copy_from_slice(&[10, 0, 0, 50]);
+        assert_eq!(
+            relay.forward_reply(&packet[..len]),
+            Some(Destination::Unicast([10, 0, 0, 50]))
+        );
+        // not a reply.
+        packet[0] = 1;
+        assert_eq!(relay.forward_reply(&packet[..len]), None);
+    }
+
+    #[test]
+    fn strip_agent_information_from_reply() {
+        let mut packet = [0; 300];
+        let len = reply(
+            &[53, 1, 5, 82, 3, 1, 1, 7, 54, 4, 10, 0, 0, 1, 0xff],
+            &mut packet,
+        );
+        let new_len = strip_agent_information(&mut packet[..len]);
+        assert_eq!(new_len, len - 5);
+        assert_eq!(
+            &packet[HEADER_LEN + 4..new_len],
+            &[53, 1, 5, 54, 4, 10, 0, 0, 1, 0xff]
+        );
+    }
+
+    #[test]
+    fn strip_every_agent_information() {
+        let mut packet = [0; 300];
+        let len = reply(
+            &[82, 3, 1, 1, 7, 53, 1, 5, 82, 2, 2, 0, 82, 0, 0xff],
+            &mut packet,
+        );
+        let new_len = strip_agent_information(&mut packet[..len]);
+        assert_eq!(new_len, len - 11);
+        assert_eq!(&packet[HEADER_LEN + 4..new_len], &[53, 1, 5, 0xff]);
+    }
+
+    #[test]
+    fn strip_truncated_agent_information() {
+        let mut packet = [0; 300];
+        let len = reply(&[53, 1, 5, 82, 200, 1, 1, 7], &mut packet);
+        assert_eq!(strip_agent_information(&mut packet[..len]), len);
+        for len in 0..len {
+            strip_agent_information(&mut packet[..len]);
+        }
+    }
+
+    #[test]
+    fn insert_agent_information_in_request() {
+        let sub_options = [RelayAgentSubOption::CircuitId(b"port1")];
+        let relay = Relay::new([10, 0, 0, 1], &[]).with_agent_information(&sub_options);
+        let mut packet = [0; 300];
+        let len = reply(&[53, 1, 1, 0xff], &mut packet);
+        let len = relay.insert_agent_information(&mut packet, len).unwrap();
+        assert_eq!(
+            &packet[HEADER_LEN + 4..len],
+            &[53, 1, 1, 82, 7, 1, 5, b'p', b'o', b'r', b't', b'1', 0xff]
+        );
+        // a truncated option , nothing is appended after it.
+        let len = reply(&[53, 1, 1, 12, 200, b'h'], &mut packet);
+        assert_eq!(relay.insert_agent_information(&mut packet, len), None);
+        // a request already padded to 300 bytes keeps its length.
+        let len = reply(&[53, 1, 1, 0xff], &mut packet);
+        packet[len..].fill(0);
+        assert_eq!(relay.insert_agent_information(&mut packet, 300), Some(300));
+        assert_eq!(
+            &packet[HEADER_LEN + 4..HEADER_LEN + 4 + 13],
+            &[53, 1, 1, 82, 7, 1, 5, b'p', b'o', b'r', b't', b'1', 0xff]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Classless Static Route option 121 (RFC 3442) , and 249 which is the same
+//! thing from Microsoft.
+//!
+//! a client receiving it must ignore option 3 and option 33.
+
+/// one route , `gateway` 0.0.0.0 means on link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub network: [u8; 4],
+    pub prefix_len: u8,
+    pub gateway: [u8; 4],
+}
+
+impl Route {
+    /// write the compact form , returns the length. bits of `network` past
+    /// `prefix_len` are written as 0.
+    fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        if self.prefix_len > 32 {
+            return None;
+        }
+        let significant = (self.prefix_len as usize).div_ceil(8);
+        let mask = u32::MAX
+            .checked_shl(32 - self.prefix_len as u32)
+            .unwrap_or(0);
+        let network = (u32::from_be_bytes(self.network) & mask).to_be_bytes();
+        let buffer = buffer.get_mut(..1 + significant + 4)?;
+        buffer[0] = self.prefix_len;
+        buffer[1..1 + significant].copy_from_slice(&network[..significant]);
+        buffer[1 + significant..].copy_from_slice(&self.gateway);
+        Some(1 + significant + 4)
+    }
+}
+
+/// value of option 121 or 249.
+#[derive(Clone, Copy, Debug)]
+pub enum ClasslessStaticRoute<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// to be sent.
+    Routes(&'a [Route]),
+}
+
+impl<'a> ClasslessStaticRoute<'a> {
+    pub fn iter(&self) -> Routes<'a> {
+        match self {
+            ClasslessStaticRoute::Bytes(x) => Routes::Bytes(x),
+            ClasslessStaticRoute::Routes(x) => Routes::Routes(x.iter()),
+        }
+    }
+
+    /// write the value (without code and length) , returns the length.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            ClasslessStaticRoute::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                Some(x.len())
+            }
+            ClasslessStaticRoute::Routes(x) => x.iter().try_fold(0, |len, route| {
+                Some(len + route.encode(buffer.get_mut(len..)?)?)
+            }),
+        }
+    }
+}
+
+pub enum Routes<'a> {
+    Bytes(&'a [u8]),
+    Routes(core::slice::Iter<'a, Route>),
+}
+
+impl Iterator for Routes<'_> {
+    type Item = Route;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Routes::Bytes(bytes) => {
+                let (&prefix_len, rest) = bytes.split_first()?;
+                let significant = (prefix_len as usize).div_ceil(8);
+                let route = rest
+                    .get(..significant + 4)
+                    .filter(|_| prefix_len <= 32)
+                    .map(|x| {
+                        let mut network = [0; 4];
+                        network[..significant].copy_from_slice(&x[..significant]);
+                        Route {
+                            network,
+                            prefix_len,
+                            gateway: [
+                                x[significant],
+                                x[significant + 1],
+                                x[significant + 2],
+                                x[significant + 3],
+                            ],
+                        }
+                    });
+                *bytes = route.map_or(&[], |_| &rest[significant + 4..]);
+                route
+            }
+            Routes::Routes(x) => x.next().copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTES: [Route; 4] = [
+        Route {
+            network: [0, 0, 0, 0],
+            prefix_len: 0,
+            gateway: [10, 0, 0, 1],
+        },
+        Route {
+            network: [10, 16, 0, 0],
+            prefix_len: 12,
+            gateway: [10, 0, 0, 2],
+        },
+        Route {
+            network: [192, 168, 1, 0],
+            prefix_len: 24,
+            gateway: [0, 0, 0, 0],
+        },
+        Route {
+            network: [10, 9, 8, 7],
+            prefix_len: 32,
+            gateway: [10, 0, 0, 3],
+        },
+    ];
+
+    /// the compact form of RFC 3442 , only the significant octets of the
+    /// network.
+    const ENCODED: &[u8] = &[
+        0, 10, 0, 0, 1, // default route
+        12, 10, 16, 10, 0, 0, 2, // 10.16.0.0/12
+        24, 192, 168, 1, 0, 0, 0, 0, // on link
+        32, 10, 9, 8, 7, 10, 0, 0, 3, // host route
+    ];
+
+    #[test]
+    fn compact_encoding() {
+        let mut buffer = [0; 64];
+        let len = ClasslessStaticRoute::Routes(&ROUTES)
+            .encode(&mut buffer)
+            .unwrap();
+        assert_eq!(&buffer[..len], ENCODED);
+        assert!(ClasslessStaticRoute::Bytes(ENCODED).iter().eq(ROUTES));
+    }
+
+    #[test]
+    fn host_bits_are_not_sent() {
+        let route = Route {
+            network: [10, 17, 1, 1],
+            ..ROUTES[1]
+        };
+        let mut buffer = [0; 64];
+        let len = ClasslessStaticRoute::Routes(&[route])
+            .encode(&mut buffer)
+            .unwrap();
+        assert_eq!(&buffer[..len], &ENCODED[5..12]);
+    }
+
+    #[test]
+    fn malformed_routes() {
+        let route = Route {
+            prefix_len: 33,
+            ..ROUTES[0]
+        };
+        let mut buffer = [0; 64];
+        assert!(ClasslessStaticRoute::Routes(&[route])
+            .encode(&mut buffer)
+            .is_none());
+        assert!(ClasslessStaticRoute::Routes(&ROUTES)
+            .encode(&mut buffer[..ENCODED.len() - 1])
+            .is_none());
+
+        // stops at the first bad route.
+        assert_eq!(
+            ClasslessStaticRoute::Bytes(&[33, 1, 2, 3, 4, 5])
+                .iter()
+                .count(),
+            0
+        );
+        for len in 0..ENCODED.len() {
+            let routes = ClasslessStaticRoute::Bytes(&ENCODED[..len]).iter();
+            assert!(routes.zip(ROUTES).all(|(x, y)| x == y));
+        }
+    }
+}
@@ -0,0 +1,418 @@
+//! server side responder (RFC 2131 4.3).
+//!
+//! like [`crate::client`] this does no I/O. the caller decides which address to
+//! give , this module builds the reply and tells where to send it.
+use crate::pool::{ClientKey, LeasePool};
+use crate::{option_list, DHCPMessage, Encapsulated, MessageTy, Op, Options, MAX_OPTIONS};
+
+/// what a client asked for.
+#[derive(Clone, Copy, Debug)]
+pub enum ClientMessage {
+    Discover {
+        requested_ip_addr: Option<[u8; 4]>,
+    },
+    /// `server_ip_addr` is set only in SELECTING state.
+    Request {
+        requested_ip_addr: [u8; 4],
+        server_ip_addr: Option<[u8; 4]>,
+    },
+    Decline {
+        ip_addr: [u8; 4],
+    },
+    Release {
+        ip_addr: [u8; 4],
+    },
+    Inform {
+        ip_addr: [u8; 4],
+    },
+}
+
+impl ClientMessage {
+    pub fn parse(message: &DHCPMessage) -> Option<Self> {
+        if !matches!(message.op, Op::BootRequest) {
+            return None;
+        }
+        let requested_ip_addr = message.requested_ip_addr();
+        Some(match message.message_type()? {
+            MessageTy::Discover => ClientMessage::Discover { requested_ip_addr },
+            MessageTy::Request => ClientMessage::Request {
+                // RENEWING and REBINDING put the address in ciaddr.
+                requested_ip_addr: requested_ip_addr.unwrap_or(message.ciaddr),
+                server_ip_addr: message.server_identifier(),
+            },
+            MessageTy::Decline => ClientMessage::Decline {
+                ip_addr: requested_ip_addr?,
+            },
+            MessageTy::Release => ClientMessage::Release {
+                ip_addr: message.ciaddr,
+            },
+            MessageTy::Inform => ClientMessage::Inform {
+                ip_addr: message.ciaddr,
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// where to send a reply (RFC 2131 4.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Destination {
+    /// to the relay agent , server port 67.
+    Relay([u8; 4]),
+    /// to an address the client already has , client port 68.
+    Unicast([u8; 4]),
+    /// to yiaddr , client port 68.
+    ///
+    /// the client can not answer ARP yet , so the link layer address must be
+    /// taken from chaddr.
+    UnicastToHardware([u8; 4]),
+    Broadcast,
+}
+
+pub struct Reply<'a> {
+    pub message: DHCPMessage<'a>,
+    pub destination: Destination,
+}
+
+/// configuration handed to clients.
+pub struct Server<'a> {
+    pub server_ip_addr: [u8; 4],
+    pub subnet_mask: [u8; 4],
+    pub router: &'a [[u8; 4]],
+    pub dns: &'a [[u8; 4]],
+    pub ntp: &'a [[u8; 4]],
+    pub domain_name: Option<&'a str>,
+    pub broadcast_addr: Option<[u8; 4]>,
+    /// option 43 , sent when requested.
+    pub vendor_specific_information: Option<Encapsulated<'a>>,
+    pub lease_time: u32,
+}
+
+impl<'a> Server<'a> {
+    /// answer `request` with addresses from `pool`.
+    pub fn handle<const N: usize>(
+        &self,
+        pool: &mut LeasePool<N>,
+        request: &DHCPMessage<'a>,
+        now: u32,
+    ) -> Option<Reply<'a>> {
+        let client = ClientKey::from_message(request);
+        match ClientMessage::parse(request)? {
+            ClientMessage::Discover { requested_ip_addr } => pool
+                .offer(&client, requested_ip_addr, now)
+                .map(|x| self.offer(request, x)),
+            ClientMessage::Request {
+                requested_ip_addr, ..
+            } => {
+                if !self.is_selected(request) {
+                    pool.cancel_offer(&client);
+                    None
+                } else if pool.commit(&client, requested_ip_addr, self.lease_time, now) {
+                    Some(self.ack(request, requested_ip_addr))
+                } else {
+                    Some(self.nak(request))
+                }
+            }
+            ClientMessage::Decline { ip_addr } => {
+                pool.decline(&client, ip_addr, now);
+                None
+            }
+            ClientMessage::Release { ip_addr } => {
+                pool.release(&client, ip_addr);
+                None
+            }
+            ClientMessage::Inform { .. } => Some(self.inform_ack(request)),
+        }
+    }
+
+    /// does `request` belong to this server ?
+    ///
+    /// false when the client selected another server.
+    pub fn is_selected(&self, request: &DHCPMessage<'a>) -> bool {
+        request
+            .server_identifier()
+            .is_none_or(|x| x == self.server_identifier(request))
+    }
+
+    /// our address , or the one a relay asked us to use (RFC 5107).
+    fn server_identifier(&self, request: &DHCPMessage<'a>) -> [u8; 4] {
+        request
+            .relay_agent_information()
+            .and_then(|x| x.server_id_override())
+            .unwrap_or(self.server_ip_addr)
+    }
+
+    pub fn offer(&self, request: &DHCPMessage<'a>, your_ip_addr: [u8; 4]) -> Reply<'a> {
+        let message = self.reply(request, MessageTy::Offer, [0; 4], your_ip_addr, true);
+        let destination = destination(request, &message, false);
+        Reply {
+            message,
+            destination,
+        }
+    }
+
+    pub fn ack(&self, request: &DHCPMessage<'a>, your_ip_addr: [u8; 4]) -> Reply<'a> {
+        let message = self.reply(request, MessageTy::Ack, request.ciaddr, your_ip_addr, true);
+        let destination = destination(request, &message, false);
+        Reply {
+            message,
+            destination,
+        }
+    }
+
+    /// ACK to INFORM , configuration only.
+    pub fn inform_ack(&self, request: &DHCPMessage<'a>) -> Reply<'a> {
+        let message = self.reply(request, MessageTy::Ack, request.ciaddr, [0; 4], false);
+        let destination = destination(request, &message, false);
+        Reply {
+            message,
+            destination,
+        }
+    }
+
+    pub fn nak(&self, request: &DHCPMessage<'a>) -> Reply<'a> {
+        // NAK carries no configuration , only the echoed option 82.
+        let mut message = self.header(request, MessageTy::Nak, [0; 4], [0; 4]);
+        if let Some(information) = request.relay_agent_information() {
+            message.insert_option(Options::RelayAgentInformation(information));
+        }
+        if message.giaddr != [0; 4] {
+            message.flags = true;
+        }
+        let destination = destination(request, &message, true);
+        Reply {
+            message,
+            destination,
+        }
+    }
+
+    fn reply(
+        &self,
+        request: &DHCPMessage<'a>,
+        message_type: MessageTy,
+        ciaddr: [u8; 4],
+        yiaddr: [u8; 4],
+        lease: bool,
+    ) -> DHCPMessage<'a> {
+        let mut message = self.header(request, message_type, ciaddr, yiaddr);
+        if lease {
+            message.insert_option(Options::IPAddressLeaseTime(self.lease_time));
+        }
+        if let Some(parameter_request_list) = request.parameter_request_list() {
+            for tag in parameter_request_list {
+                if message.options.iter().any(|x| x.tag_number() == *tag) {
+                    continue;
+                }
+                if let Some(option) = self.option(*tag, lease) {
+                    message.insert_option(option);
+                }
+            }
+        }
+        // RFC 3046 : echoed as the last option , in place of a requested one if full.
+        if let Some(information) = request.relay_agent_information() {
+            let information = Options::RelayAgentInformation(information);
+            if message.insert_option(information).is_none() {
+                if let Some(options) = message.options.list_mut() {
+                    options[MAX_OPTIONS - 2] = information;
+                }
+            }
+        }
+        message
+    }
+
+    /// message type and server identifier.
+    fn header(
+        &self,
+        request: &DHCPMessage<'a>,
+        message_type: MessageTy,
+        ciaddr: [u8; 4],
+        yiaddr: [u8; 4],
+    ) -> DHCPMessage<'a> {
+        DHCPMessage {
+            op: Op::BootReply,
+            htype: request.htype,
+            hlen: request.hlen,
+            hops: 0,
+            xid: request.xid,
+            secs: 0,
+            flags: request.flags,
+            ciaddr,
+            yiaddr,
+            siaddr: [0; 4],
+            giaddr: request.giaddr,
+            chaddr: request.chaddr,
+            sname: [0; 64],
+            file: [0; 128],
+            options: option_list(&[
+                Options::DHCPMessageType(message_type),
+                Options::ServerIdentifer(self.server_identifier(request)),
+            ]),
+        }
+    }
+
+    /// option to answer a parameter request with.
+    fn option(&self, tag: u8, lease: bool) -> Option<Options<'a>> {
+        match tag {
+            1 => Some(Options::SubNetMask(self.subnet_mask)),
+            3 if !self.router.is_empty() => Some(Options::Router(self.router)),
+            6 if !self.dns.is_empty() => Some(Options::DNS(self.dns)),
+            15 => self.domain_name.map(Options::DomainName),
+            28 => self.broadcast_addr.map(Options::BroadCastAddress),
+            42 if !self.ntp.is_empty() => Some(Options::NTPServer(self.ntp)),
+            43 => self
+                .vendor_specific_information
+                .map(Options::VendorSpecificInformation),
+            58 if lease => Some(Options::RenewalTime(self.lease_time / 2)),
+            59 if lease => Some(Options::RebindingTime(self.lease_time / 8 * 7)),
+            _ => None,
+        }
+    }
+}
+
+fn destination(request: &DHCPMessage, reply: &DHCPMessage, nak: bool) -> Destination {
+    if request.giaddr != [0; 4] {
+        Destination::Relay(request.giaddr)
+    } else if nak {
+        Destination::Broadcast
+    } else if request.ciaddr != [0; 4] {
+        Destination::Unicast(request.ciaddr)
+    } else if request.flags {
+        Destination::Broadcast
+    } else {
+        Destination::UnicastToHardware(reply.yiaddr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ClientConfig;
+    use crate::relay::{RelayAgentInformation, RelayAgentSubOption};
+    use crate::{HType, HwAddress};
+
+    const SERVER: Server<'static> = Server {
+        server_ip_addr: [192, 168, 0, 1],
+        subnet_mask: [255, 255, 255, 0],
+        router: &[[192, 168, 0, 1]],
+        dns: &[],
+        ntp: &[],
+        domain_name: None,
+        broadcast_addr: None,
+        vendor_specific_information: None,
+        lease_time: 600,
+    };
+    const RELAY: [u8; 4] = [10, 0, 0, 1];
+    const CIRCUIT: [RelayAgentSubOption; 1] = [RelayAgentSubOption::CircuitId(b"port1")];
+
+    fn config() -> ClientConfig<'static> {
+        ClientConfig {
+            parameter_request_list: &[1, 3],
+            ..ClientConfig::new(HwAddress::Ethernet(&[2, 0, 0, 0, 0, 1]), HType::Ethernet)
+        }
+    }
+
+    fn pool() -> LeasePool<'static, 8> {
+        LeasePool::new([192, 168, 0, 100], [192, 168, 0, 107], &[])
+    }
+
+    /// tags up to End.
+    fn tags<'a>(message: &'a DHCPMessage) -> impl Iterator<Item = u8> + 'a {
+        message
+            .options
+            .iter()
+            .map(|x| x.tag_number())
+            .take_while(|x| *x != 255)
+    }
+
+    #[test]
+    fn discover_offer_request_ack() {
+        let mut pool = pool();
+        let offer = SERVER.handle(&mut pool, &config().discover(1), 0).unwrap();
+        assert_eq!(offer.message.message_type(), Some(MessageTy::Offer));
+        assert_eq!(offer.message.xid, 1);
+        assert_eq!(offer.message.yiaddr, [192, 168, 0, 100]);
+        assert_eq!(
+            offer.message.server_identifier(),
+            Some(SERVER.server_ip_addr)
+        );
+        assert!(tags(&offer.message).eq([53, 54, 51, 1, 3]));
+        let request = config().request(1, [192, 168, 0, 100], SERVER.server_ip_addr);
+        let ack = SERVER.handle(&mut pool, &request, 0).unwrap();
+        assert_eq!(ack.message.message_type(), Some(MessageTy::Ack));
+        assert_eq!(ack.message.yiaddr, [192, 168, 0, 100]);
+        assert_eq!(
+            pool.lease(&ClientKey::from_message(&request), 0),
+            Some([192, 168, 0, 100])
+        );
+        // another server was selected.
+        let request = config().request(2, [192, 168, 0, 100], [192, 168, 0, 2]);
+        assert!(SERVER.handle(&mut pool, &request, 0).is_none());
+    }
+
+    #[test]
+    fn request_nak() {
+        let mut pool = pool();
+        let request = config().request(1, [192, 168, 1, 100], SERVER.server_ip_addr);
+        let nak = SERVER.handle(&mut pool, &request, 0).unwrap();
+        assert_eq!(nak.message.message_type(), Some(MessageTy::Nak));
+        assert_eq!(nak.message.yiaddr, [0; 4]);
+        // no configuration , even when requested.
+        assert!(tags(&nak.message).eq([53, 54]));
+        assert_eq!(nak.destination, Destination::Broadcast);
+    }
+
+    #[test]
+    fn destination_choice() {
+        let mut pool = pool();
+        // broadcast flag.
+        let offer = SERVER.handle(&mut pool, &config().discover(1), 0).unwrap();
+        assert_eq!(offer.destination, Destination::Broadcast);
+        let config = ClientConfig {
+            broadcast: false,
+            ..config()
+        };
+        let offer = SERVER.handle(&mut pool, &config.discover(1), 0).unwrap();
+        assert_eq!(
+            offer.destination,
+            Destination::UnicastToHardware([192, 168, 0, 100])
+        );
+        // ciaddr , RENEWING.
+        let request = config.request(1, [192, 168, 0, 100], SERVER.server_ip_addr);
+        SERVER.handle(&mut pool, &request, 0).unwrap();
+        let renew = config.renew(1, [192, 168, 0, 100]);
+        let ack = SERVER.handle(&mut pool, &renew, 10).unwrap();
+        assert_eq!(ack.message.message_type(), Some(MessageTy::Ack));
+        assert_eq!(ack.destination, Destination::Unicast([192, 168, 0, 100]));
+        // giaddr wins , and a NAK through a relay is broadcast by the relay.
+        let mut request = config.request(1, [192, 168, 1, 100], SERVER.server_ip_addr);
+        request.giaddr = RELAY;
+        let nak = SERVER.handle(&mut pool, &request, 10).unwrap();
+        assert_eq!(nak.destination, Destination::Relay(RELAY));
+        assert!(nak.message.flags);
+        let mut discover = config.discover(2);
+        discover.giaddr = RELAY;
+        let offer = SERVER.handle(&mut pool, &discover, 10).unwrap();
+        assert_eq!(offer.destination, Destination::Relay(RELAY));
+        assert!(!offer.message.flags);
+    }
+
+    #[test]
+    fn agent_information_echoed() {
+        let mut pool = pool();
+        let information =
+            Options::RelayAgentInformation(RelayAgentInformation::SubOptions(&CIRCUIT));
+        let mut discover = config().discover(1);
+        discover.giaddr = RELAY;
+        discover.insert_option(information).unwrap();
+        let offer = SERVER.handle(&mut pool, &discover, 0).unwrap();
+        assert!(tags(&offer.message).eq([53, 54, 51, 1, 3, 82]));
+        let mut request = config().request(1, [192, 168, 1, 100], SERVER.server_ip_addr);
+        request.giaddr = RELAY;
+        request.insert_option(information).unwrap();
+        let nak = SERVER.handle(&mut pool, &request, 0).unwrap();
+        assert_eq!(nak.message.message_type(), Some(MessageTy::Nak));
+        assert!(tags(&nak.message).eq([53, 54, 82]));
+        let echoed = nak.message.relay_agent_information().unwrap();
+        assert_eq!(echoed.circuit_id(), Some(&b"port1"[..]));
+    }
+}
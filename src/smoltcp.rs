@@ -0,0 +1,459 @@
+//! drive [`Client`] over a smoltcp UDP socket , in place of smoltcp's own DHCP
+//! socket.
+//!
+//! call [`SmoltcpClient::poll`] after every `Interface::poll` , and again at
+//! [`SmoltcpClient::poll_at`]. the address , default route and classless
+//! static routes are applied to the interface , DNS servers are in the
+//! [`Lease`] of [`Event::Configured`].
+//!
+//! what the interface can not hold is left out , the [`Lease`] has it all :
+//! - on link routes (gateway 0.0.0.0) , smoltcp routes always go through a
+//!   router.
+//! - routes past the 4 of [`Lease::routes`] , or past the room of the route
+//!   table. smoltcp holds `IFACE_MAX_ROUTE_COUNT` routes , 2 by default ,
+//!   the default route included.
+//! - the address , when the interface already has `IFACE_MAX_ADDR_COUNT`.
+use ::smoltcp::iface::{self, Interface, SocketHandle, SocketSet};
+use ::smoltcp::socket::udp;
+use ::smoltcp::time::Instant;
+use ::smoltcp::wire::{IpAddress, IpCidr, IpEndpoint, Ipv4Address, Ipv4Cidr};
+
+use crate::client::{Client, Event, Lease};
+use crate::server::Destination;
+use crate::transport::{DhcpClient, DhcpTransport};
+
+/// [`DhcpTransport`] over a UDP socket , never blocks.
+pub struct SmoltcpTransport<'s, 'b> {
+    pub socket: &'s mut udp::Socket<'b>,
+    /// source of the datagrams , 0.0.0.0 until configured.
+    pub local_addr: Ipv4Address,
+    pub now: Instant,
+}
+
+impl DhcpTransport for SmoltcpTransport<'_, '_> {
+    type Error = udp::SendError;
+
+    fn send(&mut self, destination: Destination, buffer: &[u8]) -> Result<(), Self::Error> {
+        let addr = match destination {
+            Destination::Relay(x) | Destination::Unicast(x) | Destination::UnicastToHardware(x) => {
+                Ipv4Address::from(x)
+            }
+            Destination::Broadcast => Ipv4Address::BROADCAST,
+        };
+        // smoltcp drops a datagram without a source address , and has none
+        // before configuration. 0.0.0.0 is what RFC 2131 4.1 asks for then.
+        let meta = udp::UdpMetadata {
+            endpoint: IpEndpoint::new(IpAddress::Ipv4(addr), 67),
+            local_address: Some(IpAddress::Ipv4(self.local_addr)),
+            meta: Default::default(),
+        };
+        match self.socket.send_slice(buffer, meta) {
+            // dropped , retransmission takes care of it.
+            Err(udp::SendError::BufferFull) => Ok(()),
+            x => x,
+        }
+    }
+
+    fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        _deadline: Option<u32>,
+    ) -> Result<Option<(usize, [u8; 4])>, Self::Error> {
+        match self.socket.recv_slice(buffer) {
+            Ok((len, meta)) => {
+                let addr = match meta.endpoint.addr {
+                    IpAddress::Ipv4(x) => x.octets(),
+                    #[allow(unreachable_patterns)]
+                    _ => [0; 4],
+                };
+                Ok(Some((len, addr)))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn now(&self) -> u32 {
+        self.now.secs() as u32
+    }
+}
+
+/// what [`SmoltcpClient`] may fail with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmoltcpError {
+    /// the socket could not be bound to port 68.
+    Bind(udp::BindError),
+    Send(udp::SendError),
+}
+
+impl From<udp::BindError> for SmoltcpError {
+    fn from(error: udp::BindError) -> Self {
+        SmoltcpError::Bind(error)
+    }
+}
+
+impl From<udp::SendError> for SmoltcpError {
+    fn from(error: udp::SendError) -> Self {
+        SmoltcpError::Send(error)
+    }
+}
+
+/// [`DhcpClient`] applying the lease to an interface.
+pub struct SmoltcpClient<'a> {
+    dhcp: DhcpClient<'a>,
+    handle: SocketHandle,
+    /// the address put on the interface.
+    cidr: Option<Ipv4Cidr>,
+    /// the static routes put on the interface , the default route aside.
+    routes: [Option<Ipv4Cidr>; 4],
+}
+
+impl<'a> SmoltcpClient<'a> {
+    /// `handle` is a UDP socket , bound here to port 68. its buffers should
+    /// hold a few [`BUFFER_LEN`](crate::client::BUFFER_LEN) datagrams.
+    ///
+    /// `seed` makes the transaction ids , e.g. from a hardware RNG.
+    pub fn new(client: Client<'a>, handle: SocketHandle, seed: u32) -> Self {
+        Self {
+            dhcp: DhcpClient::new(client, seed),
+            handle,
+            cidr: None,
+            routes: [None; 4],
+        }
+    }
+
+    pub fn client(&self) -> &Client<'a> {
+        self.dhcp.client()
+    }
+
+    /// when to call [`SmoltcpClient::poll`] again , None when bound for good.
+    pub fn poll_at(&self) -> Option<Instant> {
+        self.dhcp.poll_at().map(Instant::from_secs)
+    }
+
+    /// receive replies , send what is due and update `iface`.
+    ///
+    /// returns the last lease change , if any. `iface` is up to date even on
+    /// error , see [`SmoltcpClient::client`] for the lease.
+    pub fn poll(
+        &mut self,
+        iface: &mut Interface,
+        sockets: &mut SocketSet,
+        now: Instant,
+    ) -> Result<Option<Event>, SmoltcpError> {
+        let socket = sockets.get_mut::<udp::Socket>(self.handle);
+        if !socket.is_open() {
+            socket.bind(68)?;
+        }
+        let mut last = None;
+        loop {
+            let mut transport = SmoltcpTransport {
+                socket,
+                local_addr: self.local_addr(),
+                now,
+            };
+            let Some(event) = self.dhcp.poll(&mut transport)? else {
+                return Ok(last);
+            };
+            match event {
+                Event::Configured(lease) => self.configure(iface, &lease),
+                Event::Deconfigured => self.deconfigure(iface),
+            }
+            last = Some(event);
+        }
+    }
+
+    /// send RELEASE for the lease , if any , and remove it from `iface`.
+    ///
+    /// the lease is removed even when RELEASE could not be sent.
+    pub fn release(
+        &mut self,
+        iface: &mut Interface,
+        sockets: &mut SocketSet,
+        now: Instant,
+    ) -> Result<(), SmoltcpError> {
+        let mut transport = SmoltcpTransport {
+            socket: sockets.get_mut::<udp::Socket>(self.handle),
+            local_addr: self.local_addr(),
+            now,
+        };
+        let released = self.dhcp.release(&mut transport);
+        self.deconfigure(iface);
+        Ok(released?)
+    }
+
+    fn local_addr(&self) -> Ipv4Address {
+        self.cidr.map_or(Ipv4Address::UNSPECIFIED, |x| x.address())
+    }
+
+    fn configure(&mut self, iface: &mut Interface, lease: &Lease) {
+        let addr = Ipv4Address::from(lease.your_ip_addr);
+        let cidr = lease
+            .subnet_mask
+            .and_then(|x| Ipv4Cidr::from_netmask(addr, x.into()).ok())
+            .unwrap_or_else(|| Ipv4Cidr::new(addr, classful_prefix_len(addr)));
+        if self.cidr != Some(cidr) {
+            self.deconfigure(iface);
+            iface.update_ip_addrs(|addrs| {
+                addrs.push(IpCidr::Ipv4(cidr)).ok();
+            });
+            self.cidr = Some(cidr);
+        }
+        // RFC 3442 : 0.0.0.0/0 of option 121 stands for option 3. on link
+        // routes are left out , see the module docs.
+        let routes = lease
+            .routes
+            .iter()
+            .flatten()
+            .filter(|x| x.gateway != [0; 4]);
+        let router = lease.router.or_else(|| {
+            routes
+                .clone()
+                .find(|x| x.prefix_len == 0)
+                .map(|x| x.gateway)
+        });
+        match router {
+            Some(router) => {
+                iface
+                    .routes_mut()
+                    .add_default_ipv4_route(router.into())
+                    .ok();
+            }
+            None => {
+                iface.routes_mut().remove_default_ipv4_route();
+            }
+        }
+        self.remove_routes(iface);
+        let routes = routes.filter(|x| x.prefix_len != 0);
+        for (route, cell) in routes.zip(self.routes.iter_mut()) {
+            let cidr = Ipv4Cidr::new(route.network.into(), route.prefix_len);
+            let route = iface::Route {
+                cidr: IpCidr::Ipv4(cidr),
+                via_router: IpAddress::Ipv4(route.gateway.into()),
+                preferred_until: None,
+                expires_at: None,
+            };
+            // the table holds IFACE_MAX_ROUTE_COUNT routes , the rest are lost.
+            iface.routes_mut().update(|x| {
+                if x.push(route).is_ok() {
+                    *cell = Some(cidr);
+                }
+            });
+        }
+    }
+
+    fn deconfigure(&mut self, iface: &mut Interface) {
+        if let Some(cidr) = self.cidr.take() {
+            iface.update_ip_addrs(|addrs| addrs.retain(|x| *x != IpCidr::Ipv4(cidr)));
+            iface.routes_mut().remove_default_ipv4_route();
+            self.remove_routes(iface);
+        }
+    }
+
+    fn remove_routes(&mut self, iface: &mut Interface) {
+        for cidr in self.routes.iter_mut().filter_map(Option::take) {
+            iface
+                .routes_mut()
+                .update(|x| x.retain(|x| x.cidr != IpCidr::Ipv4(cidr)));
+        }
+    }
+}
+
+/// prefix length when the server did not send a subnet mask.
+fn classful_prefix_len(addr: Ipv4Address) -> u8 {
+    match addr.octets()[0] {
+        0..=127 => 8,
+        128..=191 => 16,
+        _ => 24,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ClientConfig;
+    use crate::route::Route;
+    use crate::{DHCPMessage, HType, HwAddress, MessageTy};
+    use ::smoltcp::iface::{Config, SocketStorage};
+    use ::smoltcp::phy::{self, Device, DeviceCapabilities};
+    use ::smoltcp::wire::{
+        EthernetAddress, EthernetFrame, EthernetProtocol, HardwareAddress, Ipv4Packet, UdpPacket,
+    };
+
+    const MAC_ADDR: [u8; 6] = [2, 0, 0, 0, 0, 1];
+
+    /// keeps the last frame sent , receives nothing.
+    struct Capture {
+        frame: [u8; 1514],
+        len: usize,
+    }
+
+    struct TxToken<'a>(&'a mut Capture);
+
+    impl phy::TxToken for TxToken<'_> {
+        fn consume<R, F>(self, len: usize, f: F) -> R
+        where
+            F: FnOnce(&mut [u8]) -> R,
+        {
+            self.0.len = len;
+            f(&mut self.0.frame[..len])
+        }
+    }
+
+    enum Never {}
+
+    impl phy::RxToken for Never {
+        fn consume<R, F>(self, _: F) -> R
+        where
+            F: FnOnce(&[u8]) -> R,
+        {
+            match self {}
+        }
+    }
+
+    impl Device for Capture {
+        type RxToken<'a> = Never;
+        type TxToken<'a> = TxToken<'a>;
+
+        fn receive(&mut self, _: Instant) -> Option<(Never, TxToken<'_>)> {
+            None
+        }
+
+        fn transmit(&mut self, _: Instant) -> Option<TxToken<'_>> {
+            Some(TxToken(self))
+        }
+
+        fn capabilities(&self) -> DeviceCapabilities {
+            let mut capabilities = DeviceCapabilities::default();
+            capabilities.max_transmission_unit = 1514;
+            capabilities
+        }
+    }
+
+    fn interface(device: &mut Capture) -> Interface {
+        let config = Config::new(HardwareAddress::Ethernet(EthernetAddress(MAC_ADDR)));
+        Interface::new(config, device, Instant::ZERO)
+    }
+
+    fn client() -> Client<'static> {
+        Client::new(ClientConfig::new(
+            HwAddress::Ethernet(&MAC_ADDR),
+            HType::Ethernet,
+        ))
+    }
+
+    #[test]
+    fn discover_from_unspecified_address() {
+        let mut device = Capture {
+            frame: [0; 1514],
+            len: 0,
+        };
+        let mut iface = interface(&mut device);
+        let mut metadata = [udp::PacketMetadata::EMPTY; 2];
+        let mut payload = [0; 2 * 1472];
+        let mut tx_metadata = [udp::PacketMetadata::EMPTY; 2];
+        let mut tx_payload = [0; 2 * 1472];
+        let socket = udp::Socket::new(
+            udp::PacketBuffer::new(&mut metadata[..], &mut payload[..]),
+            udp::PacketBuffer::new(&mut tx_metadata[..], &mut tx_payload[..]),
+        );
+        let mut storage = [SocketStorage::EMPTY; 1];
+        let mut sockets = SocketSet::new(&mut storage[..]);
+        let handle = sockets.add(socket);
+        let mut client = SmoltcpClient::new(client(), handle, 1);
+
+        assert!(matches!(
+            client.poll(&mut iface, &mut sockets, Instant::ZERO),
+            Ok(None)
+        ));
+        iface.poll(Instant::ZERO, &mut device, &mut sockets);
+        let frame = EthernetFrame::new_checked(&device.frame[..device.len]).unwrap();
+        assert_eq!(frame.ethertype(), EthernetProtocol::Ipv4);
+        let packet = Ipv4Packet::new_checked(frame.payload()).unwrap();
+        assert_eq!(packet.src_addr(), Ipv4Address::UNSPECIFIED);
+        assert_eq!(packet.dst_addr(), Ipv4Address::BROADCAST);
+        let datagram = UdpPacket::new_checked(packet.payload()).unwrap();
+        assert_eq!((datagram.src_port(), datagram.dst_port()), (68, 67));
+        let mut buffer = [0; 1472 * 3 / 2];
+        let message = DHCPMessage::decode(datagram.payload(), &mut buffer).unwrap();
+        assert_eq!(message.message_type(), Some(MessageTy::Discover));
+    }
+
+    #[test]
+    fn lease_applied_to_interface() {
+        let mut device = Capture {
+            frame: [0; 1514],
+            len: 0,
+        };
+        let mut iface = interface(&mut device);
+        let mut storage = [SocketStorage::EMPTY; 1];
+        let mut sockets = SocketSet::new(&mut storage[..]);
+        let mut metadata = [udp::PacketMetadata::EMPTY; 1];
+        let mut payload = [0; 64];
+        let mut tx_metadata = [udp::PacketMetadata::EMPTY; 1];
+        let mut tx_payload = [0; 64];
+        let handle = sockets.add(udp::Socket::new(
+            udp::PacketBuffer::new(&mut metadata[..], &mut payload[..]),
+            udp::PacketBuffer::new(&mut tx_metadata[..], &mut tx_payload[..]),
+        ));
+        let mut client = SmoltcpClient::new(client(), handle, 1);
+        let route = |network, prefix_len, gateway| {
+            Some(Route {
+                network,
+                prefix_len,
+                gateway,
+            })
+        };
+        let lease = Lease {
+            your_ip_addr: [10, 0, 0, 100],
+            server_ip_addr: [10, 0, 0, 1],
+            subnet_mask: Some([255, 255, 255, 0]),
+            router: None,
+            dns_servers: [None; 3],
+            routes: [
+                route([0, 0, 0, 0], 0, [10, 0, 0, 1]),
+                route([192, 168, 1, 0], 24, [0, 0, 0, 0]),
+                route([10, 16, 0, 0], 12, [10, 0, 0, 2]),
+                route([10, 9, 8, 7], 32, [10, 0, 0, 3]),
+            ],
+            lease_time: Some(600),
+            renewal_time: None,
+            rebinding_time: None,
+        };
+        client.configure(&mut iface, &lease);
+        assert_eq!(
+            iface.ip_addrs(),
+            [IpCidr::Ipv4(Ipv4Cidr::new(
+                Ipv4Address::new(10, 0, 0, 100),
+                24
+            ))]
+        );
+        // the default route and one more fill the table , the on link route
+        // is left out.
+        let mut routes = [None; 2];
+        iface.routes_mut().update(|x| {
+            x.iter()
+                .zip(routes.iter_mut())
+                .for_each(|(x, cell)| *cell = Some((x.cidr, x.via_router)));
+            assert_eq!(x.len(), 2);
+        });
+        assert_eq!(
+            routes,
+            [
+                Some((
+                    IpCidr::Ipv4(Ipv4Cidr::new(Ipv4Address::UNSPECIFIED, 0)),
+                    IpAddress::v4(10, 0, 0, 1)
+                )),
+                Some((
+                    IpCidr::Ipv4(Ipv4Cidr::new(Ipv4Address::new(10, 16, 0, 0), 12)),
+                    IpAddress::v4(10, 0, 0, 2)
+                )),
+            ]
+        );
+
+        client
+            .release(&mut iface, &mut sockets, Instant::ZERO)
+            .unwrap();
+        assert!(iface.ip_addrs().is_empty());
+        iface.routes_mut().update(|x| assert!(x.is_empty()));
+    }
+}
@@ -0,0 +1,144 @@
+//! blocking driver over [`std::net::UdpSocket`].
+//!
+//! the read timeout of the socket wakes the client up for retransmission and
+//! renewal. ports and the broadcast address can be changed with
+//! [`SocketSettings`] , so it runs without privileges on loopback against a
+//! local responder.
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::Instant;
+
+use crate::client::{Client, Event};
+use crate::server::Destination;
+use crate::transport::{timeout, DhcpClient, DhcpTransport, SocketSettings};
+
+/// [`DhcpTransport`] over a UDP socket.
+pub struct StdTransport {
+    socket: UdpSocket,
+    settings: SocketSettings,
+    /// the time the client counts from.
+    start: Instant,
+}
+
+impl StdTransport {
+    /// bind 0.0.0.0:68 , this usually needs privileges.
+    pub fn new() -> io::Result<Self> {
+        Self::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 68)))
+    }
+
+    /// bind `addr` instead of the client port.
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_broadcast(true)?;
+        Ok(Self {
+            socket,
+            settings: SocketSettings::default(),
+            start: Instant::now(),
+        })
+    }
+
+    /// send as `settings` say instead of to 255.255.255.255:67.
+    pub fn with_settings(mut self, settings: SocketSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl DhcpTransport for StdTransport {
+    type Error = io::Error;
+
+    fn send(&mut self, destination: Destination, buffer: &[u8]) -> io::Result<()> {
+        self.socket
+            .send_to(buffer, self.settings.remote_addr(destination))?;
+        Ok(())
+    }
+
+    fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        deadline: Option<u32>,
+    ) -> io::Result<Option<(usize, [u8; 4])>> {
+        self.socket
+            .set_read_timeout(timeout(deadline, self.now()))?;
+        match self.socket.recv_from(buffer) {
+            Ok((len, addr)) => {
+                let addr = match addr.ip() {
+                    IpAddr::V4(x) => x.octets(),
+                    IpAddr::V6(_) => [0; 4],
+                };
+                Ok(Some((len, addr)))
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn now(&self) -> u32 {
+        self.start.elapsed().as_secs() as u32
+    }
+}
+
+/// [`DhcpClient`] with its [`StdTransport`].
+pub struct StdDhcpClient<'a> {
+    dhcp: DhcpClient<'a>,
+    transport: StdTransport,
+}
+
+impl<'a> StdDhcpClient<'a> {
+    /// bind 0.0.0.0:68 , this usually needs privileges.
+    ///
+    /// `seed` makes the transaction ids.
+    pub fn new(client: Client<'a>, seed: u32) -> io::Result<Self> {
+        Self::bind(client, SocketAddr::from((Ipv4Addr::UNSPECIFIED, 68)), seed)
+    }
+
+    /// bind `addr` instead of the client port.
+    pub fn bind(client: Client<'a>, addr: SocketAddr, seed: u32) -> io::Result<Self> {
+        Ok(Self {
+            dhcp: DhcpClient::new(client, seed),
+            transport: StdTransport::bind(addr)?,
+        })
+    }
+
+    /// see [`StdTransport::with_settings`].
+    pub fn with_settings(mut self, settings: SocketSettings) -> Self {
+        self.transport = self.transport.with_settings(settings);
+        self
+    }
+
+    pub fn client(&self) -> &Client<'a> {
+        self.dhcp.client()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.transport.local_addr()
+    }
+
+    /// block until the lease changes.
+    pub fn next_event(&mut self) -> io::Result<Event> {
+        self.dhcp.next_event(&mut self.transport)
+    }
+
+    /// call `on_event` for every lease change , returns only on error.
+    pub fn run(&mut self, mut on_event: impl FnMut(&Event)) -> io::Result<()> {
+        loop {
+            on_event(&self.next_event()?);
+        }
+    }
+
+    /// send RELEASE for the lease , if any.
+    pub fn release(&mut self) -> io::Result<()> {
+        self.dhcp.release(&mut self.transport)
+    }
+}
@@ -0,0 +1,166 @@
+//! async driver over [`tokio::net::UdpSocket`].
+//!
+//! [`TokioDhcpClient::run`] sends lease changes to a channel until shutdown ,
+//! then gives the lease back. ports and addresses can be changed with
+//! [`SocketSettings`].
+use core::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use ::tokio::net::UdpSocket;
+use ::tokio::sync::mpsc;
+use ::tokio::time::Instant;
+
+use crate::client::{Client, Event};
+use crate::server::Destination;
+use crate::transport::{timeout, AsyncDhcpTransport, DhcpClient, SocketSettings};
+
+/// [`AsyncDhcpTransport`] over a tokio UDP socket.
+pub struct TokioTransport {
+    socket: UdpSocket,
+    settings: SocketSettings,
+    /// the time the client counts from.
+    start: Instant,
+}
+
+impl TokioTransport {
+    /// bind 0.0.0.0:68 , this usually needs privileges.
+    pub async fn new() -> io::Result<Self> {
+        Self::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 68))).await
+    }
+
+    /// bind `addr` instead of the client port.
+    pub async fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr).await?;
+        socket.set_broadcast(true)?;
+        Ok(Self {
+            socket,
+            settings: SocketSettings::default(),
+            start: Instant::now(),
+        })
+    }
+
+    /// send as `settings` say instead of to 255.255.255.255:67.
+    pub fn with_settings(mut self, settings: SocketSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl AsyncDhcpTransport for TokioTransport {
+    type Error = io::Error;
+
+    async fn send(&mut self, destination: Destination, buffer: &[u8]) -> io::Result<()> {
+        self.socket
+            .send_to(buffer, self.settings.remote_addr(destination))
+            .await?;
+        Ok(())
+    }
+
+    async fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        deadline: Option<u32>,
+    ) -> io::Result<Option<(usize, [u8; 4])>> {
+        let received = match timeout(deadline, self.now()) {
+            Some(x) => match ::tokio::time::timeout(x, self.socket.recv_from(buffer)).await {
+                Ok(received) => received?,
+                Err(_) => return Ok(None),
+            },
+            None => self.socket.recv_from(buffer).await?,
+        };
+        let addr = match received.1.ip() {
+            IpAddr::V4(x) => x.octets(),
+            IpAddr::V6(_) => [0; 4],
+        };
+        Ok(Some((received.0, addr)))
+    }
+
+    fn now(&self) -> u32 {
+        self.start.elapsed().as_secs() as u32
+    }
+}
+
+/// [`DhcpClient`] with its [`TokioTransport`].
+pub struct TokioDhcpClient<'a> {
+    dhcp: DhcpClient<'a>,
+    transport: TokioTransport,
+}
+
+impl<'a> TokioDhcpClient<'a> {
+    /// bind 0.0.0.0:68 , this usually needs privileges.
+    ///
+    /// `seed` makes the transaction ids.
+    pub async fn new(client: Client<'a>, seed: u32) -> io::Result<Self> {
+        Self::bind(client, SocketAddr::from((Ipv4Addr::UNSPECIFIED, 68)), seed).await
+    }
+
+    /// bind `addr` instead of the client port.
+    pub async fn bind(client: Client<'a>, addr: SocketAddr, seed: u32) -> io::Result<Self> {
+        Ok(Self {
+            dhcp: DhcpClient::new(client, seed),
+            transport: TokioTransport::bind(addr).await?,
+        })
+    }
+
+    /// see [`TokioTransport::with_settings`].
+    pub fn with_settings(mut self, settings: SocketSettings) -> Self {
+        self.transport = self.transport.with_settings(settings);
+        self
+    }
+
+    pub fn client(&self) -> &Client<'a> {
+        self.dhcp.client()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.transport.local_addr()
+    }
+
+    /// wait until the lease changes.
+    ///
+    /// cancel safe , a message being sent may be lost and is retransmitted.
+    pub async fn next_event(&mut self) -> io::Result<Event> {
+        self.dhcp.next_event_async(&mut self.transport).await
+    }
+
+    /// send every lease change to `events` until `shutdown` completes , then
+    /// give the lease back.
+    ///
+    /// a closed channel does not stop the client , a full one does not hold
+    /// up shutdown. the lease is also given back before an error is returned.
+    pub async fn run(
+        &mut self,
+        events: mpsc::Sender<Event>,
+        shutdown: impl Future<Output = ()>,
+    ) -> io::Result<()> {
+        ::tokio::pin!(shutdown);
+        loop {
+            let event = ::tokio::select! {
+                event = self.next_event() => event,
+                _ = &mut shutdown => break,
+            };
+            let event = match event {
+                Ok(event) => event,
+                Err(error) => {
+                    self.release().await.ok();
+                    return Err(error);
+                }
+            };
+            ::tokio::select! {
+                sent = events.send(event) => sent.ok(),
+                _ = &mut shutdown => break,
+            };
+        }
+        self.release().await
+    }
+
+    /// send RELEASE for the lease , if any.
+    pub async fn release(&mut self) -> io::Result<()> {
+        self.dhcp.release_async(&mut self.transport).await
+    }
+}
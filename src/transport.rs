@@ -0,0 +1,646 @@
+//! one client loop for every network stack.
+//!
+//! a transport sends and receives the datagrams of the client port and tells
+//! the time in seconds. [`DhcpClient`] runs [`Client`] over a blocking
+//! [`DhcpTransport`] or an [`AsyncDhcpTransport`]. [`MemoryTransport`] keeps
+//! datagrams in memory , to test an application without sockets.
+//! [`SocketSettings`] are where the std and tokio transports send to.
+use core::convert::Infallible;
+use core::future::Future;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use crate::client::{Client, Event, BUFFER_LEN};
+use crate::driver::{Driver, Output};
+use crate::server::Destination;
+
+pub trait DhcpTransport {
+    type Error;
+
+    /// send `buffer` to the server port of `destination`.
+    fn send(&mut self, destination: Destination, buffer: &[u8]) -> Result<(), Self::Error>;
+
+    /// receive a datagram , waiting at most until `deadline` , for ever when
+    /// None.
+    ///
+    /// returns its length and source address , None at the deadline. non
+    /// blocking transports may return None at once.
+    fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        deadline: Option<u32>,
+    ) -> Result<Option<(usize, [u8; 4])>, Self::Error>;
+
+    /// seconds from any epoch.
+    fn now(&self) -> u32;
+}
+
+/// async flavor of [`DhcpTransport`].
+pub trait AsyncDhcpTransport {
+    type Error;
+
+    /// send `buffer` to the server port of `destination`.
+    fn send(
+        &mut self,
+        destination: Destination,
+        buffer: &[u8],
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// receive a datagram , waiting until `deadline` , for ever when None.
+    ///
+    /// returns its length and source address , None at the deadline.
+    fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        deadline: Option<u32>,
+    ) -> impl Future<Output = Result<Option<(usize, [u8; 4])>, Self::Error>>;
+
+    /// seconds from any epoch.
+    fn now(&self) -> u32;
+}
+
+/// [`Client`] with retransmission , renewal and its buffers.
+pub struct DhcpClient<'a> {
+    driver: Driver<'a>,
+    buffer: [u8; BUFFER_LEN],
+    decode_buffer: [u8; BUFFER_LEN * 3 / 2],
+}
+
+impl<'a> DhcpClient<'a> {
+    /// `seed` makes the transaction ids , e.g. from a hardware RNG.
+    pub fn new(client: Client<'a>, seed: u32) -> Self {
+        Self {
+            driver: Driver::new(client, seed),
+            buffer: [0; BUFFER_LEN],
+            decode_buffer: [0; BUFFER_LEN * 3 / 2],
+        }
+    }
+
+    pub fn client(&self) -> &Client<'a> {
+        &self.driver.client
+    }
+
+    /// when [`DhcpClient::poll`] has something to do , None when bound for
+    /// good.
+    pub fn poll_at(&self) -> Option<u32> {
+        self.driver.poll_at()
+    }
+
+    /// send what is due , then receive until the next deadline.
+    ///
+    /// returns at the first lease change , or None when the transport has
+    /// nothing more or something is due.
+    pub fn poll<T: DhcpTransport>(&mut self, transport: &mut T) -> Result<Option<Event>, T::Error> {
+        let output = self.driver.poll(transport.now());
+        if let Some(event) = self.output(transport, output)? {
+            return Ok(Some(event));
+        }
+        while let Some((len, _)) = transport.recv(&mut self.buffer, self.driver.poll_at())? {
+            let output = self.driver.receive(
+                &self.buffer[..len],
+                &mut self.decode_buffer,
+                transport.now(),
+            );
+            if let Some(event) = self.output(transport, output)? {
+                return Ok(Some(event));
+            }
+            // traffic of other clients must not hold off retransmission and
+            // renewal.
+            if self.is_due(transport.now()) {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    /// block until the lease changes.
+    pub fn next_event<T: DhcpTransport>(&mut self, transport: &mut T) -> Result<Event, T::Error> {
+        loop {
+            if let Some(event) = self.poll(transport)? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// send RELEASE for the lease , if any.
+    pub fn release<T: DhcpTransport>(&mut self, transport: &mut T) -> Result<(), T::Error> {
+        let output = self.driver.release();
+        self.output(transport, output).map(|_| ())
+    }
+
+    /// wait until the lease changes.
+    ///
+    /// cancel safe , a message being sent may be lost and is retransmitted.
+    pub async fn next_event_async<T: AsyncDhcpTransport>(
+        &mut self,
+        transport: &mut T,
+    ) -> Result<Event, T::Error> {
+        loop {
+            let output = self.driver.poll(transport.now());
+            if let Some(event) = self.output_async(transport, output).await? {
+                return Ok(event);
+            }
+            loop {
+                let deadline = self.driver.poll_at();
+                let Some((len, _)) = transport.recv(&mut self.buffer, deadline).await? else {
+                    break;
+                };
+                let output = self.driver.receive(
+                    &self.buffer[..len],
+                    &mut self.decode_buffer,
+                    transport.now(),
+                );
+                if let Some(event) = self.output_async(transport, output).await? {
+                    return Ok(event);
+                }
+                // as in poll , other clients must not hold off the timers.
+                if self.is_due(transport.now()) {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// send RELEASE for the lease , if any.
+    pub async fn release_async<T: AsyncDhcpTransport>(
+        &mut self,
+        transport: &mut T,
+    ) -> Result<(), T::Error> {
+        let output = self.driver.release();
+        self.output_async(transport, output).await.map(|_| ())
+    }
+
+    fn is_due(&self, now: u32) -> bool {
+        self.driver.poll_at().is_some_and(|x| now >= x)
+    }
+
+    fn output<T: DhcpTransport>(
+        &mut self,
+        transport: &mut T,
+        output: Output,
+    ) -> Result<Option<Event>, T::Error> {
+        if let Some((message, destination)) = output.send {
+            if let Some(len) = message.encode(&mut self.buffer) {
+                transport.send(destination, &self.buffer[..len])?;
+            }
+        }
+        Ok(output.event)
+    }
+
+    async fn output_async<T: AsyncDhcpTransport>(
+        &mut self,
+        transport: &mut T,
+        output: Output<'_>,
+    ) -> Result<Option<Event>, T::Error> {
+        if let Some((message, destination)) = output.send {
+            if let Some(len) = message.encode(&mut self.buffer) {
+                transport.send(destination, &self.buffer[..len]).await?;
+            }
+        }
+        Ok(output.event)
+    }
+}
+
+/// where a socket transport sends , changed to run without privileges on
+/// loopback against a local responder.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SocketSettings {
+    pub server_port: u16,
+    pub broadcast_addr: Ipv4Addr,
+    /// None sends unicasts to the server identifier.
+    pub server_addr: Option<Ipv4Addr>,
+}
+
+#[cfg(feature = "std")]
+impl Default for SocketSettings {
+    fn default() -> Self {
+        Self {
+            server_port: 67,
+            broadcast_addr: Ipv4Addr::BROADCAST,
+            server_addr: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl SocketSettings {
+    /// send to `server_port` instead of 67.
+    pub fn with_server_port(mut self, server_port: u16) -> Self {
+        self.server_port = server_port;
+        self
+    }
+
+    /// send broadcasts to `broadcast_addr` instead of 255.255.255.255 ,
+    /// e.g. 127.0.0.1 for a local responder.
+    pub fn with_broadcast_addr(mut self, broadcast_addr: Ipv4Addr) -> Self {
+        self.broadcast_addr = broadcast_addr;
+        self
+    }
+
+    /// send unicasts to `server_addr` instead of the server identifier.
+    pub fn with_server_addr(mut self, server_addr: Ipv4Addr) -> Self {
+        self.server_addr = Some(server_addr);
+        self
+    }
+
+    /// the socket address of `destination`.
+    pub fn remote_addr(&self, destination: Destination) -> SocketAddrV4 {
+        let addr = match destination {
+            Destination::Relay(x) | Destination::Unicast(x) | Destination::UnicastToHardware(x) => {
+                self.server_addr.unwrap_or(Ipv4Addr::from(x))
+            }
+            Destination::Broadcast => self.broadcast_addr,
+        };
+        SocketAddrV4::new(addr, self.server_port)
+    }
+}
+
+/// how long to wait at `now` for `deadline` , at least a second.
+#[cfg(feature = "std")]
+pub(crate) fn timeout(deadline: Option<u32>, now: u32) -> Option<Duration> {
+    deadline.map(|x| Duration::from_secs(x.saturating_sub(now).max(1) as u64))
+}
+
+/// datagrams in memory , up to `N` each way , more are dropped.
+///
+/// push the replies of a server with [`MemoryTransport::push_received`] ,
+/// take what the client sent with [`MemoryTransport::pop_sent`]. the time
+/// moves with [`MemoryTransport::advance`] , and to the deadline of an async
+/// receive with nothing to receive.
+pub struct MemoryTransport<const N: usize> {
+    now: u32,
+    received: Queue<[u8; 4], N>,
+    sent: Queue<Destination, N>,
+}
+
+impl<const N: usize> MemoryTransport<N> {
+    pub fn new(now: u32) -> Self {
+        Self {
+            now,
+            received: Queue::new([0; 4]),
+            sent: Queue::new(Destination::Broadcast),
+        }
+    }
+
+    pub fn now(&self) -> u32 {
+        self.now
+    }
+
+    pub fn advance(&mut self, secs: u32) {
+        self.now = self.now.saturating_add(secs);
+    }
+
+    /// a datagram from `source` for the client , false when full or longer
+    /// than [`BUFFER_LEN`].
+    pub fn push_received(&mut self, source: [u8; 4], datagram: &[u8]) -> bool {
+        self.received.push(source, datagram)
+    }
+
+    /// the oldest datagram sent by the client , copied into `buffer`.
+    pub fn pop_sent(&mut self, buffer: &mut [u8]) -> Option<(usize, Destination)> {
+        self.sent.pop(buffer)
+    }
+}
+
+impl<const N: usize> DhcpTransport for MemoryTransport<N> {
+    type Error = Infallible;
+
+    fn send(&mut self, destination: Destination, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.sent.push(destination, buffer);
+        Ok(())
+    }
+
+    /// never waits.
+    fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        _deadline: Option<u32>,
+    ) -> Result<Option<(usize, [u8; 4])>, Self::Error> {
+        Ok(self.received.pop(buffer))
+    }
+
+    fn now(&self) -> u32 {
+        self.now
+    }
+}
+
+impl<const N: usize> AsyncDhcpTransport for MemoryTransport<N> {
+    type Error = Infallible;
+
+    async fn send(&mut self, destination: Destination, buffer: &[u8]) -> Result<(), Self::Error> {
+        DhcpTransport::send(self, destination, buffer)
+    }
+
+    /// with nothing to receive , the time moves to `deadline` as if it was
+    /// waited for. pending for ever without a deadline.
+    async fn recv(
+        &mut self,
+        buffer: &mut [u8],
+        deadline: Option<u32>,
+    ) -> Result<Option<(usize, [u8; 4])>, Self::Error> {
+        let received = self.received.pop(buffer);
+        if received.is_none() {
+            match deadline {
+                Some(x) => self.now = self.now.max(x),
+                None => core::future::pending().await,
+            }
+        }
+        Ok(received)
+    }
+
+    fn now(&self) -> u32 {
+        self.now
+    }
+}
+
+/// fixed capacity FIFO of datagrams with an address.
+struct Queue<A, const N: usize> {
+    datagrams: [(A, usize, [u8; BUFFER_LEN]); N],
+    head: usize,
+    len: usize,
+}
+
+impl<A: Copy, const N: usize> Queue<A, N> {
+    fn new(addr: A) -> Self {
+        Self {
+            datagrams: [(addr, 0, [0; BUFFER_LEN]); N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, addr: A, datagram: &[u8]) -> bool {
+        if self.len == N || datagram.len() > BUFFER_LEN {
+            return false;
+        }
+        let (cell_addr, cell_len, data) = &mut self.datagrams[(self.head + self.len) % N];
+        *cell_addr = addr;
+        *cell_len = datagram.len();
+        data[..datagram.len()].copy_from_slice(datagram);
+        self.len += 1;
+        true
+    }
+
+    /// truncated when `buffer` is short.
+    fn pop(&mut self, buffer: &mut [u8]) -> Option<(usize, A)> {
+        if self.len == 0 {
+            return None;
+        }
+        let (addr, len, data) = &self.datagrams[self.head];
+        let len = (*len).min(buffer.len());
+        buffer[..len].copy_from_slice(&data[..len]);
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some((len, *addr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::client::{ClientConfig, State};
+    use crate::pool::LeasePool;
+    use crate::server::Server;
+    use crate::{DHCPMessage, HType, HwAddress};
+
+    const SERVER: Server<'static> = Server {
+        server_ip_addr: [192, 168, 0, 1],
+        subnet_mask: [255, 255, 255, 0],
+        router: &[[192, 168, 0, 1]],
+        dns: &[[192, 168, 0, 1]],
+        ntp: &[],
+        domain_name: None,
+        broadcast_addr: None,
+        vendor_specific_information: None,
+        lease_time: 600,
+    };
+
+    /// answer what the client sent , returns where the last reply went.
+    fn serve<const N: usize>(
+        transport: &mut MemoryTransport<N>,
+        pool: &mut LeasePool<8>,
+    ) -> Option<Destination> {
+        let mut buffer = [0; BUFFER_LEN];
+        let mut sent = None;
+        while let Some((len, destination)) = transport.pop_sent(&mut buffer) {
+            sent = Some(destination);
+            let mut decode_buffer = [0; BUFFER_LEN * 3 / 2];
+            let request = DHCPMessage::decode(&buffer[..len], &mut decode_buffer)?;
+            let reply = SERVER.handle(pool, &request, transport.now())?;
+            let mut reply_buffer = [0; BUFFER_LEN];
+            let len = reply.message.encode(&mut reply_buffer)?;
+            transport.push_received(SERVER.server_ip_addr, &reply_buffer[..len]);
+        }
+        sent
+    }
+
+    #[test]
+    fn lease_and_renewal() {
+        let mac_addr = [2, 0, 0, 0, 0, 1];
+        let config = ClientConfig {
+            parameter_request_list: &[1, 3, 6, 51, 58, 59],
+            ..ClientConfig::new(HwAddress::Ethernet(&mac_addr), HType::Ethernet)
+        };
+        let mut client = DhcpClient::new(Client::new(config), 1);
+        let mut transport: MemoryTransport<4> = MemoryTransport::new(0);
+        let mut pool = LeasePool::new([192, 168, 0, 100], [192, 168, 0, 107], &[]);
+
+        // DISCOVER , OFFER , REQUEST , ACK.
+        assert!(client.poll(&mut transport).unwrap().is_none());
+        assert_eq!(client.client().state(), State::Selecting);
+        assert_eq!(
+            serve(&mut transport, &mut pool),
+            Some(Destination::Broadcast)
+        );
+        assert!(client.poll(&mut transport).unwrap().is_none());
+        assert_eq!(client.client().state(), State::Requesting);
+        assert_eq!(
+            serve(&mut transport, &mut pool),
+            Some(Destination::Broadcast)
+        );
+        let Some(Event::Configured(lease)) = client.poll(&mut transport).unwrap() else {
+            panic!("no lease");
+        };
+        assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+        assert_eq!(lease.server_ip_addr, SERVER.server_ip_addr);
+        assert_eq!(lease.router, Some([192, 168, 0, 1]));
+        assert_eq!(lease.renew_after(), Some(300));
+        assert_eq!(client.poll_at(), Some(300));
+
+        // nothing to do before T1.
+        transport.advance(299);
+        assert!(client.poll(&mut transport).unwrap().is_none());
+        assert_eq!(serve(&mut transport, &mut pool), None);
+
+        // REQUEST to the server at T1 , then ACK.
+        transport.advance(1);
+        assert!(client.poll(&mut transport).unwrap().is_none());
+        assert_eq!(client.client().state(), State::Renewing);
+        assert_eq!(
+            serve(&mut transport, &mut pool),
+            Some(Destination::Unicast(SERVER.server_ip_addr))
+        );
+        let Some(Event::Configured(lease)) = client.poll(&mut transport).unwrap() else {
+            panic!("not renewed");
+        };
+        assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+        assert_eq!(client.client().state(), State::Bound);
+        assert_eq!(client.poll_at(), Some(600));
+    }
+
+    /// [`SERVER`] behind a [`MemoryTransport`] , with an OFFER for another
+    /// client every second until `busy_until`.
+    struct Segment {
+        transport: MemoryTransport<4>,
+        pool: LeasePool<'static, 8>,
+        busy_until: u32,
+        foreign: [u8; BUFFER_LEN],
+        foreign_len: usize,
+        /// messages of the client while busy.
+        sent_while_busy: usize,
+    }
+
+    impl Segment {
+        fn new(busy_until: u32) -> Self {
+            let mac_addr = [2, 0, 0, 0, 0, 2];
+            let config = ClientConfig::new(HwAddress::Ethernet(&mac_addr), HType::Ethernet);
+            let offer = SERVER.offer(&config.discover(7), [192, 168, 0, 107]);
+            let mut foreign = [0; BUFFER_LEN];
+            let foreign_len = offer.message.encode(&mut foreign).unwrap();
+            Self {
+                transport: MemoryTransport::new(0),
+                pool: LeasePool::new([192, 168, 0, 100], [192, 168, 0, 106], &[]),
+                busy_until,
+                foreign,
+                foreign_len,
+                sent_while_busy: 0,
+            }
+        }
+
+        fn send(&mut self, destination: Destination, buffer: &[u8]) {
+            if self.transport.now < self.busy_until {
+                self.sent_while_busy += 1;
+            }
+            self.transport.sent.push(destination, buffer);
+            serve(&mut self.transport, &mut self.pool);
+        }
+
+        fn foreign(&mut self, buffer: &mut [u8]) -> Option<(usize, [u8; 4])> {
+            if self.transport.now >= self.busy_until {
+                return None;
+            }
+            self.transport.advance(1);
+            buffer[..self.foreign_len].copy_from_slice(&self.foreign[..self.foreign_len]);
+            Some((self.foreign_len, [192, 168, 0, 254]))
+        }
+    }
+
+    impl DhcpTransport for Segment {
+        type Error = Infallible;
+
+        fn send(&mut self, destination: Destination, buffer: &[u8]) -> Result<(), Infallible> {
+            Segment::send(self, destination, buffer);
+            Ok(())
+        }
+
+        fn recv(
+            &mut self,
+            buffer: &mut [u8],
+            deadline: Option<u32>,
+        ) -> Result<Option<(usize, [u8; 4])>, Infallible> {
+            match self.foreign(buffer) {
+                Some(x) => Ok(Some(x)),
+                None => DhcpTransport::recv(&mut self.transport, buffer, deadline),
+            }
+        }
+
+        fn now(&self) -> u32 {
+            self.transport.now
+        }
+    }
+
+    impl AsyncDhcpTransport for Segment {
+        type Error = Infallible;
+
+        async fn send(
+            &mut self,
+            destination: Destination,
+            buffer: &[u8],
+        ) -> Result<(), Infallible> {
+            Segment::send(self, destination, buffer);
+            Ok(())
+        }
+
+        async fn recv(
+            &mut self,
+            buffer: &mut [u8],
+            deadline: Option<u32>,
+        ) -> Result<Option<(usize, [u8; 4])>, Infallible> {
+            match self.foreign(buffer) {
+                Some(x) => Ok(Some(x)),
+                None => AsyncDhcpTransport::recv(&mut self.transport, buffer, deadline).await,
+            }
+        }
+
+        fn now(&self) -> u32 {
+            self.transport.now
+        }
+    }
+
+    /// run `future` , the transports here never wait for real.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(x) = future.as_mut().poll(&mut cx) {
+                return x;
+            }
+        }
+    }
+
+    #[test]
+    fn retransmit_on_busy_segment() {
+        let mac_addr = [2, 0, 0, 0, 0, 1];
+        let config = ClientConfig::new(HwAddress::Ethernet(&mac_addr), HType::Ethernet);
+        let mut client = DhcpClient::new(Client::new(config), 1);
+        let mut segment = Segment::new(35);
+        let event = (0..100).find_map(|_| client.poll(&mut segment).unwrap());
+        assert!(matches!(event, Some(Event::Configured(_))));
+        // DISCOVER at 0 , 10 , 20 and 30.
+        assert_eq!(segment.sent_while_busy, 4);
+    }
+
+    #[test]
+    fn async_lease_and_renewal() {
+        let mac_addr = [2, 0, 0, 0, 0, 1];
+        let config = ClientConfig {
+            parameter_request_list: &[1, 3, 6, 51, 58, 59],
+            ..ClientConfig::new(HwAddress::Ethernet(&mac_addr), HType::Ethernet)
+        };
+        let mut client = DhcpClient::new(Client::new(config), 1);
+        let mut segment = Segment::new(35);
+
+        let Event::Configured(lease) = block_on(client.next_event_async(&mut segment)).unwrap()
+        else {
+            panic!("no lease");
+        };
+        assert_eq!(segment.sent_while_busy, 4);
+        assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+
+        // the time moves to T1 , REQUEST and ACK.
+        let bound_at = segment.transport.now;
+        let Event::Configured(lease) = block_on(client.next_event_async(&mut segment)).unwrap()
+        else {
+            panic!("not renewed");
+        };
+        assert_eq!(segment.transport.now, bound_at + 300);
+        assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+        assert_eq!(client.client().state(), State::Bound);
+    }
+}
@@ -0,0 +1,233 @@
+//! Vendor-Identifying options 124 and 125 (RFC 3925).
+//!
+//! both carry blocks keyed by IANA enterprise number , so several vendors can
+//! share one message.
+use crate::Encapsulated;
+
+/// value of option 124.
+#[derive(Clone, Copy, Debug)]
+pub enum VendorClass<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// (enterprise number , class) to be sent , e.g. a model string.
+    Classes(&'a [(u32, &'a str)]),
+}
+
+impl<'a> VendorClass<'a> {
+    pub fn iter(&self) -> VendorClasses<'a> {
+        match self {
+            VendorClass::Bytes(x) => VendorClasses::Bytes(VendorBlocks { bytes: x }),
+            VendorClass::Classes(x) => VendorClasses::Classes(x.iter()),
+        }
+    }
+
+    /// write the value (without code and length) , returns the length.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            VendorClass::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                Some(x.len())
+            }
+            VendorClass::Classes(x) => x.iter().try_fold(0, |len, (enterprise, class)| {
+                let buffer = buffer.get_mut(len..len + 6 + class.len())?;
+                buffer[0..4].copy_from_slice(&enterprise.to_be_bytes());
+                buffer[4] = u8::try_from(class.len() + 1).ok()?;
+                buffer[5] = class.len() as u8;
+                buffer[6..].copy_from_slice(class.as_bytes());
+                Some(len + 6 + class.len())
+            }),
+        }
+    }
+}
+
+pub enum VendorClasses<'a> {
+    Bytes(VendorBlocks<'a>),
+    Classes(core::slice::Iter<'a, (u32, &'a str)>),
+}
+
+impl<'a> Iterator for VendorClasses<'a> {
+    type Item = (u32, VendorClassData<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            VendorClasses::Bytes(x) => x
+                .next()
+                .map(|(enterprise, data)| (enterprise, VendorClassData::Bytes(data))),
+            VendorClasses::Classes(x) => x
+                .next()
+                .map(|(enterprise, class)| (*enterprise, VendorClassData::One(Some(class)))),
+        }
+    }
+}
+
+/// opaque class data of one vendor.
+pub enum VendorClassData<'a> {
+    /// length prefixed items.
+    Bytes(&'a [u8]),
+    One(Option<&'a str>),
+}
+
+impl<'a> Iterator for VendorClassData<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            VendorClassData::Bytes(bytes) => {
+                let (&len, rest) = bytes.split_first()?;
+                let Some((data, rest)) = rest.split_at_checked(len as usize) else {
+                    *bytes = &[];
+                    return None;
+                };
+                *bytes = rest;
+                Some(data)
+            }
+            VendorClassData::One(x) => x.take().map(str::as_bytes),
+        }
+    }
+}
+
+/// value of option 125.
+#[derive(Clone, Copy, Debug)]
+pub enum VendorInformation<'a> {
+    /// as received.
+    Bytes(&'a [u8]),
+    /// (enterprise number , sub-options) to be sent.
+    Vendors(&'a [(u32, Encapsulated<'a>)]),
+}
+
+impl<'a> VendorInformation<'a> {
+    pub fn iter(&self) -> VendorInformations<'a> {
+        match self {
+            VendorInformation::Bytes(x) => VendorInformations::Bytes(VendorBlocks { bytes: x }),
+            VendorInformation::Vendors(x) => VendorInformations::Vendors(x.iter()),
+        }
+    }
+
+    /// sub-options of `enterprise`.
+    pub fn get(&self, enterprise: u32) -> Option<Encapsulated<'a>> {
+        self.iter().find(|(x, _)| *x == enterprise).map(|(_, x)| x)
+    }
+
+    /// write the value (without code and length) , returns the length.
+    pub(crate) fn encode(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            VendorInformation::Bytes(x) => {
+                buffer.get_mut(..x.len())?.copy_from_slice(x);
+                Some(x.len())
+            }
+            VendorInformation::Vendors(x) => x.iter().try_fold(0, |len, (enterprise, data)| {
+                let buffer = buffer.get_mut(len..)?;
+                let data_len = data.encode(buffer.get_mut(5..)?)?;
+                buffer[0..4].copy_from_slice(&enterprise.to_be_bytes());
+                buffer[4] = u8::try_from(data_len).ok()?;
+                Some(len + 5 + data_len)
+            }),
+        }
+    }
+}
+
+pub enum VendorInformations<'a> {
+    Bytes(VendorBlocks<'a>),
+    Vendors(core::slice::Iter<'a, (u32, Encapsulated<'a>)>),
+}
+
+impl<'a> Iterator for VendorInformations<'a> {
+    type Item = (u32, Encapsulated<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            VendorInformations::Bytes(x) => x
+                .next()
+                .map(|(enterprise, data)| (enterprise, Encapsulated::Bytes(data))),
+            VendorInformations::Vendors(x) => x.next().copied(),
+        }
+    }
+}
+
+/// (enterprise number , data) blocks as received.
+pub struct VendorBlocks<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for VendorBlocks<'a> {
+    type Item = (u32, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let [a, b, c, d, len, rest @ ..] = self.bytes else {
+            self.bytes = &[];
+            return None;
+        };
+        let Some((data, rest)) = rest.split_at_checked(*len as usize) else {
+            self.bytes = &[];
+            return None;
+        };
+        self.bytes = rest;
+        Some((u32::from_be_bytes([*a, *b, *c, *d]), data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::reply;
+    use crate::DHCPMessage;
+
+    #[test]
+    fn vendor_class_encoding() {
+        let classes = [(4491, "modem"), (9, "ab")];
+        let mut buffer = [0; 32];
+        let len = VendorClass::Classes(&classes).encode(&mut buffer).unwrap();
+        assert_eq!(
+            buffer[..len],
+            [0, 0, 0x11, 0x8b, 6, 5, b'm', b'o', b'd', b'e', b'm', 0, 0, 0, 9, 3, 2, b'a', b'b']
+        );
+        let (enterprise, data) = VendorClass::Bytes(&buffer[..len]).iter().next().unwrap();
+        assert_eq!(enterprise, 4491);
+        assert!(data.eq([&b"modem"[..]]));
+        assert!(VendorClass::Classes(&classes[..1])
+            .encode(&mut buffer[..10])
+            .is_none());
+    }
+
+    #[test]
+    fn vendor_information_encoding() {
+        let sub_options: [(u8, &[u8]); 2] = [(1, &[7]), (2, b"xy")];
+        let vendors = [(4491, Encapsulated::SubOptions(&sub_options))];
+        let mut buffer = [0; 32];
+        let len = VendorInformation::Vendors(&vendors)
+            .encode(&mut buffer)
+            .unwrap();
+        assert_eq!(
+            buffer[..len],
+            [0, 0, 0x11, 0x8b, 7, 1, 1, 7, 2, 2, b'x', b'y']
+        );
+        let information = VendorInformation::Bytes(&buffer[..len]);
+        let data = information.get(4491).unwrap();
+        assert!(data.iter().eq(sub_options));
+        assert!(information.get(9).is_none());
+    }
+
+    #[test]
+    fn truncated_vendor_blocks() {
+        let mut packet = [0; 600];
+        // the second class and block are longer than what is left.
+        let len = reply(
+            &[
+                124, 13, 0, 0, 0, 9, 3, 2, b'a', b'b', 0, 0, 0, 10, 4, 125, 8, 0, 0, 0, 9, 1, 0, 0,
+                0, 0xff,
+            ],
+            &mut packet,
+        );
+        let mut buffer = [0; 900];
+        let message = DHCPMessage::decode(&packet[..len], &mut buffer).unwrap();
+        let class = message.vendor_identifying_vendor_class().unwrap();
+        let mut classes = class.iter();
+        let (enterprise, data) = classes.next().unwrap();
+        assert_eq!(enterprise, 9);
+        assert!(data.eq([&b"ab"[..]]));
+        assert!(classes.next().is_none());
+        let information = message.vendor_identifying_vendor_information().unwrap();
+        assert!(information.get(9).is_some());
+        assert_eq!(information.iter().count(), 1);
+    }
+}
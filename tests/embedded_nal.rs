@@ -0,0 +1,150 @@
+//! [`NalClient`] over std-embedded-nal on loopback , against a local
+//! responder.
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use embedded_dhcp_client::client::{Client, ClientConfig, Event, State, BUFFER_LEN};
+use embedded_dhcp_client::embedded_nal::NalClient;
+use embedded_dhcp_client::pool::LeasePool;
+use embedded_dhcp_client::server::Server;
+use embedded_dhcp_client::{DHCPMessage, HType, HwAddress};
+use embedded_nal::{nb, UdpClientStack, UdpFullStack};
+use std_embedded_nal::Stack;
+
+/// [`Stack`] binding any free port for 68 and sending everything for port 67
+/// to `server_addr` , so no privileges are needed.
+struct LoopbackStack {
+    stack: Stack,
+    server_addr: SocketAddr,
+}
+
+impl UdpClientStack for LoopbackStack {
+    type UdpSocket = <Stack as UdpClientStack>::UdpSocket;
+    type Error = io::Error;
+
+    fn socket(&mut self) -> io::Result<Self::UdpSocket> {
+        self.stack.socket()
+    }
+
+    fn connect(&mut self, socket: &mut Self::UdpSocket, remote: SocketAddr) -> io::Result<()> {
+        self.stack.connect(socket, remote)
+    }
+
+    fn send(&mut self, socket: &mut Self::UdpSocket, buffer: &[u8]) -> nb::Result<(), io::Error> {
+        self.stack.send(socket, buffer)
+    }
+
+    fn receive(
+        &mut self,
+        socket: &mut Self::UdpSocket,
+        buffer: &mut [u8],
+    ) -> nb::Result<(usize, SocketAddr), io::Error> {
+        self.stack.receive(socket, buffer)
+    }
+
+    fn close(&mut self, socket: Self::UdpSocket) -> io::Result<()> {
+        self.stack.close(socket)
+    }
+}
+
+impl UdpFullStack for LoopbackStack {
+    fn bind(&mut self, socket: &mut Self::UdpSocket, local_port: u16) -> io::Result<()> {
+        assert_eq!(local_port, 68);
+        self.stack.bind(socket, 0)
+    }
+
+    fn send_to(
+        &mut self,
+        socket: &mut Self::UdpSocket,
+        remote: SocketAddr,
+        buffer: &[u8],
+    ) -> nb::Result<(), io::Error> {
+        assert_eq!(remote.port(), 67);
+        self.stack.send_to(socket, self.server_addr, buffer)
+    }
+}
+
+/// answer every request on `socket` , to the port it came from.
+fn respond(socket: UdpSocket) {
+    let server = Server {
+        server_ip_addr: [192, 168, 0, 1],
+        subnet_mask: [255, 255, 255, 0],
+        router: &[[192, 168, 0, 1]],
+        dns: &[[192, 168, 0, 1]],
+        ntp: &[],
+        domain_name: None,
+        broadcast_addr: None,
+        vendor_specific_information: None,
+        lease_time: 4,
+    };
+    let mut pool: LeasePool<8> = LeasePool::new([192, 168, 0, 100], [192, 168, 0, 107], &[]);
+    let start = Instant::now();
+    let mut recv_buffer = [0; BUFFER_LEN];
+    let mut send_buffer = [0; BUFFER_LEN];
+    loop {
+        let Ok((len, client_addr)) = socket.recv_from(&mut recv_buffer) else {
+            return;
+        };
+        let mut decode_buffer = [0; BUFFER_LEN * 3 / 2];
+        let Some(request) = DHCPMessage::decode(&recv_buffer[..len], &mut decode_buffer) else {
+            continue;
+        };
+        let now = start.elapsed().as_secs() as u32;
+        if let Some(reply) = server.handle(&mut pool, &request, now) {
+            let len = reply.message.encode(&mut send_buffer).unwrap();
+            socket.send_to(&send_buffer[..len], client_addr).unwrap();
+        }
+    }
+}
+
+/// poll until the lease changes , for at most 10 seconds.
+fn next_event(client: &mut NalClient<LoopbackStack>, stack: &mut LoopbackStack) -> Event {
+    let start = Instant::now();
+    loop {
+        let now = start.elapsed().as_secs() as u32;
+        match client.poll(stack, now) {
+            Ok(event) => return event,
+            Err(nb::Error::WouldBlock) => std::thread::sleep(Duration::from_millis(10)),
+            Err(nb::Error::Other(e)) => panic!("{e}"),
+        }
+        assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+    }
+}
+
+#[test]
+fn lease_and_renewal() {
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    // the sockets of the stack are IPv6 , reaching IPv4 through mapped
+    // addresses.
+    let server_port = socket.local_addr().unwrap().port();
+    let mut stack = LoopbackStack {
+        stack: Stack,
+        server_addr: SocketAddr::new(
+            IpAddr::V6(Ipv4Addr::LOCALHOST.to_ipv6_mapped()),
+            server_port,
+        ),
+    };
+    std::thread::spawn(move || respond(socket));
+
+    let mac_addr = [2, 0, 0, 0, 0, 1];
+    let config = ClientConfig {
+        parameter_request_list: &[1, 3, 6, 51, 58, 59],
+        ..ClientConfig::new(HwAddress::Ethernet(&mac_addr), HType::Ethernet)
+    };
+    let mut client = NalClient::new(&mut stack, Client::new(config), 1).unwrap();
+
+    let Event::Configured(lease) = next_event(&mut client, &mut stack) else {
+        panic!("no lease");
+    };
+    assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+    assert_eq!(lease.server_ip_addr, [192, 168, 0, 1]);
+
+    let Event::Configured(lease) = next_event(&mut client, &mut stack) else {
+        panic!("not renewed");
+    };
+    assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+    assert_eq!(client.client().state(), State::Bound);
+
+    client.close(&mut stack).unwrap();
+}
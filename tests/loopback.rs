@@ -0,0 +1,143 @@
+//! [`StdDhcpClient`] against a responder on loopback , no privileges needed.
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::mpsc;
+use std::time::Instant;
+
+use embedded_dhcp_client::client::{Client, ClientConfig, Event, State, BUFFER_LEN};
+use embedded_dhcp_client::pool::LeasePool;
+use embedded_dhcp_client::server::Server;
+use embedded_dhcp_client::std_net::StdDhcpClient;
+use embedded_dhcp_client::transport::SocketSettings;
+use embedded_dhcp_client::{DHCPMessage, HType, HwAddress, MessageTy};
+
+/// answer every request on `socket` , replies go to `client_addr`. the type
+/// of each request goes to `requests`.
+fn respond(socket: UdpSocket, client_addr: SocketAddr, requests: mpsc::Sender<MessageTy>) {
+    let server = Server {
+        server_ip_addr: [192, 168, 0, 1],
+        subnet_mask: [255, 255, 255, 0],
+        router: &[[192, 168, 0, 1]],
+        dns: &[[192, 168, 0, 1]],
+        ntp: &[],
+        domain_name: None,
+        broadcast_addr: None,
+        vendor_specific_information: None,
+        lease_time: 4,
+    };
+    let mut pool: LeasePool<8> = LeasePool::new([192, 168, 0, 100], [192, 168, 0, 107], &[]);
+    let start = Instant::now();
+    let mut recv_buffer = [0; BUFFER_LEN];
+    let mut send_buffer = [0; BUFFER_LEN];
+    loop {
+        let Ok((len, _)) = socket.recv_from(&mut recv_buffer) else {
+            return;
+        };
+        let mut decode_buffer = [0; BUFFER_LEN * 3 / 2];
+        let Some(request) = DHCPMessage::decode(&recv_buffer[..len], &mut decode_buffer) else {
+            continue;
+        };
+        if let Some(message_type) = request.message_type() {
+            requests.send(message_type).ok();
+        }
+        let now = start.elapsed().as_secs() as u32;
+        if let Some(reply) = server.handle(&mut pool, &request, now) {
+            let len = reply.message.encode(&mut send_buffer).unwrap();
+            socket.send_to(&send_buffer[..len], client_addr).unwrap();
+        }
+    }
+}
+
+#[test]
+fn lease_and_renewal() {
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let server_port = socket.local_addr().unwrap().port();
+
+    let mac_addr = [2, 0, 0, 0, 0, 1];
+    let config = ClientConfig {
+        parameter_request_list: &[1, 3, 6, 51, 58, 59],
+        ..ClientConfig::new(HwAddress::Ethernet(&mac_addr), HType::Ethernet)
+    }
+    .with_receive_buffer_len(BUFFER_LEN);
+    let mut client = StdDhcpClient::bind(
+        Client::new(config),
+        SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+        1,
+    )
+    .unwrap()
+    .with_settings(
+        SocketSettings::default()
+            .with_server_port(server_port)
+            .with_broadcast_addr(Ipv4Addr::LOCALHOST)
+            .with_server_addr(Ipv4Addr::LOCALHOST),
+    );
+    let client_addr = client.local_addr().unwrap();
+    let (requests, _) = mpsc::channel();
+    std::thread::spawn(move || respond(socket, client_addr, requests));
+
+    let Event::Configured(lease) = client.next_event().unwrap() else {
+        panic!("no lease");
+    };
+    assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+    assert_eq!(lease.router, Some([192, 168, 0, 1]));
+    assert_eq!(lease.lease_time, Some(4));
+
+    // renewed by unicast at T1 , before the lease expires.
+    let Event::Configured(lease) = client.next_event().unwrap() else {
+        panic!("not renewed");
+    };
+    assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+    assert_eq!(client.client().state(), State::Bound);
+
+    client.release().unwrap();
+    assert_eq!(client.client().state(), State::Init);
+}
+
+/// the tokio client keeps its lease until shutdown , even when nobody reads
+/// the events , then gives it back.
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn run_until_shutdown() {
+    use embedded_dhcp_client::tokio::TokioDhcpClient;
+    use std::time::Duration;
+
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let server_port = socket.local_addr().unwrap().port();
+
+    let mac_addr = [2, 0, 0, 0, 0, 1];
+    let config = ClientConfig::new(HwAddress::Ethernet(&mac_addr), HType::Ethernet);
+    let mut client = TokioDhcpClient::bind(
+        Client::new(config),
+        SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+        1,
+    )
+    .await
+    .unwrap()
+    .with_settings(
+        SocketSettings::default()
+            .with_server_port(server_port)
+            .with_broadcast_addr(Ipv4Addr::LOCALHOST)
+            .with_server_addr(Ipv4Addr::LOCALHOST),
+    );
+    let client_addr = client.local_addr().unwrap();
+    let (requests, requested) = mpsc::channel();
+    std::thread::spawn(move || respond(socket, client_addr, requests));
+
+    // the first lease fills the channel , the renewal at T1 can not be sent.
+    let (events, mut received) = ::tokio::sync::mpsc::channel(1);
+    let shutdown = ::tokio::time::sleep(Duration::from_secs(3));
+    ::tokio::time::timeout(Duration::from_secs(10), client.run(events, shutdown))
+        .await
+        .expect("shutdown held up")
+        .unwrap();
+    assert_eq!(client.client().state(), State::Init);
+    let Ok(Event::Configured(lease)) = received.try_recv() else {
+        panic!("no lease");
+    };
+    assert_eq!(lease.your_ip_addr, [192, 168, 0, 100]);
+    assert!(received.try_recv().is_err());
+
+    assert_eq!(requested.try_recv(), Ok(MessageTy::Discover));
+    let released = std::iter::from_fn(|| requested.recv_timeout(Duration::from_secs(1)).ok())
+        .any(|x| x == MessageTy::Release);
+    assert!(released);
+}